@@ -1,4 +1,12 @@
-use crate::{state::App, ui::fx::FxManager};
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    backend::BackendCommand,
+    form::{EndpointForm, PromptKind, SilencePrompt},
+    silence::SilenceKind,
+    state::{App, StatusLevel, View},
+    ui::fx::FxManager,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Action {
@@ -6,6 +14,20 @@ pub enum Action {
     MoveUp,
     MoveDown,
     ToggleInspect,
+    CheckNow,
+    TogglePause,
+    TogglePauseAll,
+    AddEndpoint,
+    EditEndpoint,
+    DeleteEndpoint,
+    ExportHistory,
+    Acknowledge,
+    Snooze,
+    ToggleMute,
+    ShowEndpoints,
+    ShowIncidents,
+    ShowAlerts,
+    Select,
     CycleSort,
     ToggleSortDirection,
     Search,
@@ -17,7 +39,16 @@ pub enum Action {
 ///
 /// 'FxManager' is being passed right now for possible future animation extensibility.
 /// I tried it on inspector but it got a bit dizzying to have it run every time.
-pub fn handle_action(action: &Action, app: &mut App, _fx_manager: &mut FxManager) -> bool {
+///
+/// 'backend_tx' is used to send commands to the endpoint workers. Sending uses
+/// 'try_send' so a busy backend can never block the UI, the worst case is a
+/// dropped key press.
+pub fn handle_action(
+    action: &Action,
+    app: &mut App,
+    _fx_manager: &mut FxManager,
+    backend_tx: &Sender<BackendCommand>,
+) -> bool {
     use Action::*;
 
     match action {
@@ -40,6 +71,116 @@ pub fn handle_action(action: &Action, app: &mut App, _fx_manager: &mut FxManager
             //     fx_manager.trigger_inspector_slide_in();
             // }
         }
+        CheckNow => {
+            let Some(name) = app.selected_endpoint_name().cloned() else {
+                return false;
+            };
+            let _ = backend_tx.try_send(BackendCommand::CheckNow(name));
+        }
+        TogglePause => {
+            let Some(name) = app.selected_endpoint_name().cloned() else {
+                return false;
+            };
+            let Some(state) = app.endpoint_states.get(&name) else {
+                return false;
+            };
+
+            let command = if state.paused {
+                BackendCommand::Resume(name.clone())
+            } else {
+                BackendCommand::Pause(name.clone())
+            };
+
+            if backend_tx.try_send(command).is_ok() {
+                let paused = !state.paused;
+                app.set_paused(&name, paused);
+            }
+        }
+        TogglePauseAll => {
+            let paused = !app.all_paused();
+            let command = if paused {
+                BackendCommand::PauseAll
+            } else {
+                BackendCommand::ResumeAll
+            };
+
+            if backend_tx.try_send(command).is_ok() {
+                app.set_all_paused(paused);
+            }
+        }
+        AddEndpoint => {
+            app.form = Some(EndpointForm::new_add());
+        }
+        EditEndpoint => {
+            let Some(endpoint) = app.selected_endpoint_config() else {
+                return false;
+            };
+            app.form = Some(EndpointForm::new_edit(endpoint));
+        }
+        DeleteEndpoint => {
+            // Deleting asks for confirmation first, see 'app::on_delete_confirm_key'
+            app.pending_delete = app.selected_endpoint_name().cloned();
+        }
+        ExportHistory => {
+            let Some(name) = app.selected_endpoint_name().cloned() else {
+                return false;
+            };
+            app.export(&name);
+        }
+        // Acking or snoozing an endpoint that already is lifts it instead
+        Acknowledge => {
+            let Some(name) = app.highlighted_endpoint() else {
+                return false;
+            };
+            let kind = app.silences.get(&name).map(|silence| &silence.kind);
+
+            if matches!(kind, Some(SilenceKind::Ack { .. })) {
+                app.unsilence(&name);
+            } else if !app.needs_attention(&name) {
+                app.set_status(
+                    StatusLevel::Info,
+                    format!("'{name}' is up, nothing to acknowledge"),
+                );
+            } else {
+                app.prompt = Some(SilencePrompt::new(PromptKind::Ack, name));
+            }
+        }
+        Snooze => {
+            let Some(name) = app.highlighted_endpoint() else {
+                return false;
+            };
+            let kind = app.silences.get(&name).map(|silence| &silence.kind);
+
+            if matches!(kind, Some(SilenceKind::Snooze { .. })) {
+                app.unsilence(&name);
+            } else {
+                app.prompt = Some(SilencePrompt::new(PromptKind::Snooze, name));
+            }
+        }
+        ToggleMute => {
+            let Some(name) = app.highlighted_endpoint() else {
+                return false;
+            };
+            let kind = app.silences.get(&name).map(|silence| &silence.kind);
+
+            if matches!(kind, Some(SilenceKind::Mute)) {
+                app.unsilence(&name);
+            } else {
+                app.silence(&name, SilenceKind::Mute);
+            }
+        }
+        ShowEndpoints => {
+            app.show_view(View::Endpoints);
+        }
+        ShowIncidents => {
+            app.show_view(View::Incidents);
+        }
+        ShowAlerts => {
+            app.show_view(View::Alerts);
+        }
+        Select => {
+            app.jump_to_selected_endpoint();
+        }
         CycleSort => {
             return false;
         }
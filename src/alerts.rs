@@ -0,0 +1,332 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    config::{AlertCondition, AlertRule},
+    state::EndpointState,
+    stats::slo::SloStatus,
+};
+
+// Alerts are evaluated per (rule, endpoint) pair every time the endpoint
+// reports a result. A matching condition first makes the alert pending,
+// it fires once the condition held for the rule's `for` duration and is
+// resolved as soon as the condition stops matching.
+
+// Only the most recently resolved alerts are kept around
+const MAX_RESOLVED_ALERTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertState {
+    Pending,
+    Firing,
+    Resolved,
+}
+
+impl AlertState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertState::Pending => "PENDING",
+            AlertState::Firing => "FIRING",
+            AlertState::Resolved => "RESOLVED",
+        }
+    }
+}
+
+/// An alert rule matching on a single endpoint.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub rule: String,
+    pub endpoint: String,
+    pub state: AlertState,
+
+    // When the condition started matching
+    pub pending_since: SystemTime,
+    pub firing_since: Option<SystemTime>,
+    pub resolved_at: Option<SystemTime>,
+
+    // Describes why the condition matched, updated on every evaluation
+    pub message: String,
+}
+
+/// Keeps track of the alerts of every rule and endpoint.
+pub struct AlertEngine {
+    // Pending and firing alerts by (rule, endpoint)
+    active: HashMap<(String, String), Alert>,
+    resolved: VecDeque<Alert>,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        AlertEngine {
+            active: HashMap::new(),
+            resolved: VecDeque::new(),
+        }
+    }
+
+    /// Evaluates every rule that applies to the endpoint.
+    ///
+    /// Alerts of flapping endpoints don't fire until the endpoint settles,
+    /// they can still resolve though.
+    pub fn evaluate(
+        &mut self,
+        now: SystemTime,
+        rules: &[AlertRule],
+        state: &EndpointState,
+        slo: Option<&SloStatus>,
+    ) {
+        for rule in rules {
+            if !rule.applies_to(&state.name, &state.tags) {
+                continue;
+            }
+
+            let key = (rule.name.clone(), state.name.clone());
+
+            let Some(message) = check_condition(&rule.condition, state, slo, now) else {
+                if let Some(mut alert) = self.active.remove(&key)
+                    && alert.state == AlertState::Firing
+                {
+                    alert.state = AlertState::Resolved;
+                    alert.resolved_at = Some(now);
+                    self.push_resolved(alert);
+                }
+                continue;
+            };
+
+            let alert = self.active.entry(key).or_insert_with(|| Alert {
+                rule: rule.name.clone(),
+                endpoint: state.name.clone(),
+                state: AlertState::Pending,
+                pending_since: now,
+                firing_since: None,
+                resolved_at: None,
+                message: String::new(),
+            });
+            alert.message = message;
+
+            let held_for = now.duration_since(alert.pending_since).unwrap_or_default();
+
+            if alert.state == AlertState::Pending
+                && held_for >= Duration::from_secs(rule.for_secs)
+                && !state.flap.is_flapping()
+            {
+                alert.state = AlertState::Firing;
+                alert.firing_since = Some(now);
+            }
+        }
+    }
+
+    /// Drops the alerts of rules and endpoints that no longer exist after
+    /// the rules went from `old_rules` to `rules`.
+    ///
+    /// Pending and firing alerts of a rule that was edited are dropped too,
+    /// the new rule starts over. Resolved ones are kept as history.
+    pub fn retain(&mut self, old_rules: &[AlertRule], rules: &[AlertRule], endpoints: &[String]) {
+        let exists = |alert: &Alert| {
+            rules.iter().any(|r| r.name == alert.rule) && endpoints.contains(&alert.endpoint)
+        };
+        let unchanged = |alert: &Alert| {
+            let old = old_rules.iter().find(|r| r.name == alert.rule);
+            let new = rules.iter().find(|r| r.name == alert.rule);
+            old.is_some() && old == new
+        };
+
+        self.active
+            .retain(|_, alert| exists(alert) && unchanged(alert));
+        self.resolved.retain(|alert| exists(alert));
+    }
+
+    /// Firing alerts first, then pending ones, then the most recently resolved.
+    ///
+    /// Alerts that started at the same time are ordered by endpoint and rule
+    /// so the order doesn't change from one call to the next.
+    pub fn all(&self) -> Vec<&Alert> {
+        let mut active: Vec<&Alert> = self.active.values().collect();
+        active.sort_by(|a, b| {
+            (b.state == AlertState::Firing)
+                .cmp(&(a.state == AlertState::Firing))
+                .then_with(|| a.pending_since.cmp(&b.pending_since))
+                .then_with(|| a.endpoint.cmp(&b.endpoint))
+                .then_with(|| a.rule.cmp(&b.rule))
+        });
+
+        active.extend(self.resolved.iter().rev());
+        active
+    }
+
+    /// True if the endpoint has a pending or firing alert.
+    pub fn has_active(&self, endpoint: &str) -> bool {
+        self.active.values().any(|alert| alert.endpoint == endpoint)
+    }
+
+    pub fn firing_count(&self) -> usize {
+        self.active
+            .values()
+            .filter(|alert| alert.state == AlertState::Firing)
+            .count()
+    }
+
+    fn push_resolved(&mut self, alert: Alert) {
+        self.resolved.push_back(alert);
+        if self.resolved.len() > MAX_RESOLVED_ALERTS {
+            self.resolved.pop_front();
+        }
+    }
+}
+
+/// Returns a description of why the condition matches, or None if it doesn't.
+fn check_condition(
+    condition: &AlertCondition,
+    state: &EndpointState,
+    slo: Option<&SloStatus>,
+    now: SystemTime,
+) -> Option<String> {
+    match condition {
+        AlertCondition::ConsecutiveFailures { count } => {
+            let failures = state.consecutive_failures;
+            (failures >= *count).then(|| format!("{failures} consecutive failures"))
+        }
+        AlertCondition::P95Above { ms } => {
+            let p95 = state.latency.window().p95?;
+            (p95 > *ms).then(|| format!("P95 {p95}ms above {ms}ms"))
+        }
+        AlertCondition::UptimeBelow { percent, window } => {
+            let uptime = state.availability_stats.uptime_percent(*window, now)?;
+            (uptime < *percent).then(|| format!("Uptime {window} {uptime:.2}% below {percent}%"))
+        }
+        AlertCondition::CertExpiring { days } => {
+            let expiry = state.cert_expiry?;
+            let left = expiry.duration_since(now).unwrap_or_default();
+            let days_left = left.as_secs() / (24 * 60 * 60);
+
+            (days_left < *days).then(|| {
+                if left.is_zero() {
+                    "Certificate expired".to_string()
+                } else {
+                    format!("Certificate expires in {days_left} days")
+                }
+            })
+        }
+        AlertCondition::Health { state: threshold } => {
+            let health = state.health?;
+            (health >= *threshold).then(|| format!("Endpoint is {}", health.label()))
+        }
+        AlertCondition::FastBurn => {
+            let slo = slo?;
+            slo.is_fast_burn().then(|| {
+                format!(
+                    "Burning the error budget at {:.1}x, {:.1}% left",
+                    slo.burn_rate_1h.unwrap_or_default(),
+                    slo.budget_remaining
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Endpoint, StatuiConfig};
+
+    fn failing_state(name: &str, failures: u64) -> EndpointState {
+        let endpoint = Endpoint {
+            name: name.to_string(),
+            url: "http://localhost".to_string(),
+            ..Endpoint::default()
+        };
+        let mut state = EndpointState::new(&endpoint, &StatuiConfig::default());
+        state.consecutive_failures = failures;
+        state
+    }
+
+    fn rule(name: &str, count: u64, for_secs: u64) -> AlertRule {
+        AlertRule {
+            name: name.to_string(),
+            endpoints: Vec::new(),
+            tags: Vec::new(),
+            condition: AlertCondition::ConsecutiveFailures { count },
+            for_secs,
+        }
+    }
+
+    #[test]
+    fn fires_after_the_for_duration_and_resolves() {
+        let rules = [rule("down", 3, 60)];
+        let mut engine = AlertEngine::new();
+        let start = SystemTime::now();
+
+        engine.evaluate(start, &rules, &failing_state("api", 2), None);
+        assert!(engine.all().is_empty());
+
+        engine.evaluate(start, &rules, &failing_state("api", 3), None);
+        assert_eq!(engine.all()[0].state, AlertState::Pending);
+
+        let later = start + Duration::from_secs(59);
+        engine.evaluate(later, &rules, &failing_state("api", 4), None);
+        assert_eq!(engine.all()[0].state, AlertState::Pending);
+
+        let later = start + Duration::from_secs(60);
+        engine.evaluate(later, &rules, &failing_state("api", 5), None);
+        assert_eq!(engine.firing_count(), 1);
+        assert_eq!(engine.all()[0].message, "5 consecutive failures");
+
+        let later = start + Duration::from_secs(90);
+        engine.evaluate(later, &rules, &failing_state("api", 0), None);
+        let alerts = engine.all();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].state, AlertState::Resolved);
+        assert_eq!(alerts[0].resolved_at, Some(later));
+    }
+
+    #[test]
+    fn pending_alerts_that_clear_arent_resolved() {
+        let rules = [rule("down", 1, 60)];
+        let mut engine = AlertEngine::new();
+        let now = SystemTime::now();
+
+        engine.evaluate(now, &rules, &failing_state("api", 1), None);
+        engine.evaluate(now, &rules, &failing_state("api", 0), None);
+        assert!(engine.all().is_empty());
+    }
+
+    #[test]
+    fn orders_alerts_that_started_together() {
+        let rules = [rule("b", 1, 60), rule("a", 1, 60)];
+        let mut engine = AlertEngine::new();
+        let now = SystemTime::now();
+
+        for name in ["web", "api"] {
+            engine.evaluate(now, &rules, &failing_state(name, 1), None);
+        }
+
+        let order: Vec<(&str, &str)> = engine
+            .all()
+            .iter()
+            .map(|alert| (alert.endpoint.as_str(), alert.rule.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [("api", "a"), ("api", "b"), ("web", "a"), ("web", "b")]
+        );
+    }
+
+    #[test]
+    fn edited_rules_start_over() {
+        let old_rules = [rule("down", 1, 0), rule("slow", 1, 0)];
+        let mut engine = AlertEngine::new();
+        let now = SystemTime::now();
+        engine.evaluate(now, &old_rules, &failing_state("api", 1), None);
+        assert_eq!(engine.firing_count(), 2);
+
+        let rules = [rule("down", 5, 0), rule("slow", 1, 0)];
+        engine.retain(&old_rules, &rules, &["api".to_string()]);
+        let alerts = engine.all();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule, "slow");
+
+        engine.retain(&rules, &rules, &[]);
+        assert!(engine.all().is_empty());
+    }
+}
@@ -0,0 +1,622 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Json, Router,
+    extract::{Path, RawQuery, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, sync::broadcast};
+use tokio_stream::{Stream, StreamExt, wrappers::BroadcastStream};
+
+use crate::{
+    backend::{CheckResult, CheckStatus},
+    config::{StatuiConfig, parse_duration},
+    history,
+    state::{self, EndpointState},
+    stats::{
+        anomaly::LatencyBaseline,
+        incidents::{self, Health, Incident},
+        latency::LatencyStats,
+        uptime::UptimeWindow,
+    },
+};
+
+// Read-only JSON API.
+//
+// Like the Prometheus exporter, the App hands every endpoint to `Api`
+// right after it records a result, so the API always agrees with the TUI.
+// New checks are also broadcast to the clients of the event stream.
+
+// Checks a slow event stream client can fall behind by before it misses some
+const EVENT_BUFFER_SIZE: usize = 256;
+
+// How far back the history route reads the history on disk without a ?since=
+const DEFAULT_HISTORY_SINCE: &str = "24h";
+
+/// What the API serves about a single endpoint.
+struct EndpointSnapshot {
+    health: Option<Health>,
+    paused: bool,
+    summary: Value,
+    // Newest first
+    checks: Vec<Value>,
+    incidents: Vec<Incident>,
+}
+
+/// The state of every endpoint, shared with the HTTP server.
+pub struct Api {
+    order: Mutex<Vec<String>>,
+    endpoints: Mutex<HashMap<String, EndpointSnapshot>>,
+    events: broadcast::Sender<Value>,
+    // What the history route reads and assesses the history on disk with
+    config: Mutex<Option<StatuiConfig>>,
+}
+
+impl Api {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Api {
+            order: Mutex::new(Vec::new()),
+            endpoints: Mutex::new(HashMap::new()),
+            events: broadcast::channel(EVENT_BUFFER_SIZE).0,
+            config: Mutex::new(None),
+        })
+    }
+
+    /// Keeps a copy of the config for the history route, called whenever it changes.
+    pub fn set_config(&self, config: &StatuiConfig) {
+        if let Ok(mut current) = self.config.lock() {
+            *current = Some(config.clone());
+        }
+    }
+
+    /// Takes a snapshot of `state`, called whenever it changes.
+    pub fn update(&self, state: &EndpointState, group: Option<&str>, now: SystemTime) {
+        let snapshot = EndpointSnapshot {
+            health: state.health,
+            paused: state.paused,
+            summary: summary(state, group, now),
+            checks: state
+                .recent_checks
+                .iter()
+                .map(|(at, result, health)| check(*at, result, *health))
+                .collect(),
+            incidents: state.incidents.all().cloned().collect(),
+        };
+
+        if let Ok(mut endpoints) = self.endpoints.lock() {
+            endpoints.insert(state.name.clone(), snapshot);
+        }
+    }
+
+    /// Sends the latest check of `state` to the event stream, right after it was recorded.
+    pub fn publish(&self, state: &EndpointState) {
+        let Some((at, result, health)) = state.recent_checks.front() else {
+            return;
+        };
+
+        let mut event = check(*at, result, *health);
+        event["health"] = json!(state.health);
+        // Fails when nobody is listening
+        let _ = self.events.send(event);
+    }
+
+    /// Keeps the endpoints in `order` in that order and drops the rest.
+    pub fn retain(&self, order: &[String]) {
+        if let Ok(mut endpoints) = self.endpoints.lock() {
+            endpoints.retain(|name, _| order.contains(name));
+        }
+        if let Ok(mut current) = self.order.lock() {
+            *current = order.to_vec();
+        }
+    }
+}
+
+/// Serves the API until the listener fails.
+///
+/// With a token, every request needs an `Authorization: Bearer <token>`
+/// header or a `?token=` query parameter (for browser event sources).
+pub async fn serve(listener: TcpListener, api: Arc<Api>, token: Option<String>) {
+    let router = Router::new()
+        .route("/api/endpoints", get(endpoints))
+        .route("/api/endpoints/{name}/history", get(history))
+        .route("/api/incidents", get(all_incidents))
+        .route("/api/events", get(events))
+        .with_state(api)
+        .layer(middleware::from_fn(move |request: Request, next: Next| {
+            let token = token.clone();
+            async move {
+                match token {
+                    Some(token) if !authorized(&request, &token) => (
+                        StatusCode::UNAUTHORIZED,
+                        [(header::WWW_AUTHENTICATE, "Bearer")],
+                        Json(json!({ "error": "missing or wrong token" })),
+                    )
+                        .into_response(),
+                    _ => next.run(request).await,
+                }
+            }
+        }));
+
+    let _ = axum::serve(listener, router).await;
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if bearer.is_some_and(|bearer| constant_time_eq(bearer, token)) {
+        return true;
+    }
+
+    // The query is percent-encoded, tokens with e.g. a '+' or '/' in them are escaped
+    query_param(request.uri().query(), "token").is_some_and(|value| constant_time_eq(&value, token))
+}
+
+/// Compares the whole string whatever the first difference is, so the time
+/// a wrong token takes doesn't tell how much of it was right.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The percent-decoded value of `key` in the query.
+fn query_param(query: Option<&str>, key: &str) -> Option<String> {
+    form_urlencoded::parse(query?.as_bytes())
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// GET /api/endpoints
+async fn endpoints(State(api): State<Arc<Api>>) -> Response {
+    let order = api
+        .order
+        .lock()
+        .map(|order| order.clone())
+        .unwrap_or_default();
+    let Ok(endpoints) = api.endpoints.lock() else {
+        return unavailable();
+    };
+
+    let snapshots: Vec<&EndpointSnapshot> = order
+        .iter()
+        .filter_map(|name| endpoints.get(name))
+        .collect();
+
+    // The worst health of the endpoints that are being checked
+    let status = snapshots
+        .iter()
+        .filter(|snapshot| !snapshot.paused)
+        .filter_map(|snapshot| snapshot.health)
+        .max();
+    let summaries: Vec<&Value> = snapshots.iter().map(|snapshot| &snapshot.summary).collect();
+
+    Json(json!({ "status": status, "endpoints": summaries })).into_response()
+}
+
+/// GET /api/endpoints/{name}/history
+///
+/// With the history on disk, the checks of the last `?since=` (a duration
+/// like `30m` or `7d`), otherwise the ones kept in memory.
+async fn history(
+    State(api): State<Arc<Api>>,
+    Path(name): Path<String>,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let (recent, incidents) = {
+        let Ok(endpoints) = api.endpoints.lock() else {
+            return unavailable();
+        };
+        let Some(snapshot) = endpoints.get(&name) else {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": format!("no endpoint named '{name}'") })),
+            )
+                .into_response();
+        };
+        let incidents: Vec<Value> = snapshot.incidents.iter().map(incident).collect();
+        (snapshot.checks.clone(), incidents)
+    };
+
+    let config = api.config.lock().ok().and_then(|config| config.clone());
+    let checks = match config {
+        Some(config) if config.history.enabled => {
+            let since = query_param(query.as_deref(), "since");
+            let since = match parse_duration(since.as_deref().unwrap_or(DEFAULT_HISTORY_SINCE)) {
+                Ok(since) => since,
+                Err(error) => {
+                    return (StatusCode::BAD_REQUEST, Json(json!({ "error": error })))
+                        .into_response();
+                }
+            };
+
+            // Reading the history can take a while, keep it off the runtime
+            let endpoint = name.clone();
+            let loaded =
+                tokio::task::spawn_blocking(move || checks_on_disk(&config, &endpoint, since));
+            match loaded.await {
+                Ok(checks) => checks,
+                Err(_) => return unavailable(),
+            }
+        }
+        _ => recent,
+    };
+
+    Json(json!({
+        "endpoint": name,
+        "checks": checks,
+        "incidents": incidents,
+    }))
+    .into_response()
+}
+
+/// The checks of the endpoint in the history from `since` ago, newest first.
+///
+/// They're assessed again like the TUI did, so slow responses are degraded too.
+fn checks_on_disk(config: &StatuiConfig, name: &str, since: Duration) -> Vec<Value> {
+    let Some(endpoint) = config.endpoints.iter().find(|e| e.name == name) else {
+        return Vec::new();
+    };
+    let since = SystemTime::now().checked_sub(since).unwrap_or(UNIX_EPOCH);
+    let thresholds = config.latency_thresholds(endpoint);
+    let mut baseline = LatencyBaseline::new();
+
+    let mut checks: Vec<Value> = history::load_since(&config.history, since)
+        .into_iter()
+        .filter(|record| record.name == name)
+        .map(|record| {
+            let at = record.time();
+            let result = record.to_result();
+            let (health, _) = state::assess_health(
+                at,
+                &result,
+                thresholds,
+                &mut baseline,
+                &config.anomaly_detection,
+            );
+            check(at, &result, health)
+        })
+        .collect();
+    checks.reverse();
+    checks
+}
+
+/// GET /api/incidents, open ones first then the most recent ones.
+async fn all_incidents(State(api): State<Arc<Api>>) -> Response {
+    let Ok(endpoints) = api.endpoints.lock() else {
+        return unavailable();
+    };
+
+    let mut all: Vec<&Incident> = endpoints
+        .values()
+        .flat_map(|snapshot| &snapshot.incidents)
+        .collect();
+    all.sort_by(|a, b| {
+        b.is_open()
+            .cmp(&a.is_open())
+            .then_with(|| b.start.cmp(&a.start))
+    });
+
+    let all: Vec<Value> = all.into_iter().map(incident).collect();
+    Json(json!({ "incidents": all })).into_response()
+}
+
+/// GET /api/events, a `check` event for every new result.
+async fn events(State(api): State<Arc<Api>>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // Clients that fell behind skip the checks they missed
+    let stream = BroadcastStream::new(api.events.subscribe()).filter_map(|check| {
+        let check = check.ok()?;
+        Some(Ok(Event::default().event("check").data(check.to_string())))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn unavailable() -> Response {
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+fn summary(state: &EndpointState, group: Option<&str>, now: SystemTime) -> Value {
+    let latest = state.recent_checks.front();
+    let code = latest.and_then(|(_, result, _)| match result.status {
+        CheckStatus::Success { code, .. } => Some(code),
+        CheckStatus::Error { .. } => None,
+    });
+
+    let uptime: serde_json::Map<String, Value> = UptimeWindow::ALL
+        .iter()
+        .map(|window| {
+            let percent = state.availability_stats.uptime_percent(*window, now);
+            (window.label().to_string(), json!(percent))
+        })
+        .collect();
+
+    json!({
+        "name": state.name,
+        "url": state.url,
+        "method": state.method,
+        "group": group,
+        "tags": state.tags,
+        "paused": state.paused,
+        "status": state.health,
+        "status_since": state.health_since.map(timestamp),
+        "reason": state.health_reason,
+        "flapping": state.flap.is_flapping(),
+        "consecutive_failures": state.consecutive_failures,
+        "last_check": latest.map(|(at, _, _)| timestamp(*at)),
+        "code": code,
+        "message": latest.map(|(_, result, _)| incidents::describe(&result.status)),
+        "latency_ms": state.latest_latency.map(|latency| latency.as_millis() as u64),
+        "latency": {
+            "window": latency(state.latency.window()),
+            "lifetime": latency(state.latency.lifetime()),
+        },
+        "uptime": uptime,
+        "mttr_secs": state.incidents.mttr().map(|mttr| mttr.as_secs()),
+        "mtbf_secs": state.incidents.mtbf().map(|mtbf| mtbf.as_secs()),
+        "cert_expiry": state.cert_expiry.map(timestamp),
+    })
+}
+
+fn latency(stats: &LatencyStats) -> Value {
+    json!({
+        "avg": stats.avg,
+        "min": stats.min,
+        "max": stats.max,
+        "p50": stats.p50,
+        "p90": stats.p90,
+        "p95": stats.p95,
+        "p99": stats.p99,
+    })
+}
+
+/// A check along with the health it was assessed as, which takes the latency
+/// thresholds and anomalies into account unlike the result alone.
+fn check(at: SystemTime, result: &CheckResult, health: Health) -> Value {
+    let code = match result.status {
+        CheckStatus::Success { code, .. } => Some(code),
+        CheckStatus::Error { .. } => None,
+    };
+
+    json!({
+        "endpoint": result.name,
+        "time": timestamp(at),
+        "status": health,
+        "code": code,
+        "error_class": result.status.error_class(),
+        "message": incidents::describe(&result.status),
+        "latency_ms": result.latency.as_millis() as u64,
+    })
+}
+
+fn incident(incident: &Incident) -> Value {
+    let now = SystemTime::now();
+    json!({
+        "endpoint": incident.endpoint,
+        "open": incident.is_open(),
+        "start": timestamp(incident.start),
+        "end": incident.end.map(timestamp),
+        "duration_secs": incident.duration(now).as_secs(),
+        "worst": incident.worst,
+        "first_error": incident.first_error,
+        "checks": incident.check_count,
+    })
+}
+
+fn timestamp(at: SystemTime) -> String {
+    DateTime::<Utc>::from(at).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use axum::body::{Body, to_bytes};
+
+    use super::*;
+    use crate::{
+        config::Endpoint,
+        history::{CheckRecord, HistoryWriter},
+    };
+
+    fn endpoint(name: &str) -> Endpoint {
+        Endpoint {
+            name: name.to_string(),
+            url: format!("https://{name}.example.com"),
+            ..Endpoint::default()
+        }
+    }
+
+    fn result(name: &str, status: CheckStatus, latency_ms: u64) -> CheckResult {
+        CheckResult {
+            name: name.to_string(),
+            status,
+            latency: Duration::from_millis(latency_ms),
+            cert_expiry: None,
+            generation: 0,
+        }
+    }
+
+    fn ok() -> CheckStatus {
+        CheckStatus::Success {
+            code: 200,
+            text: "OK".to_string(),
+        }
+    }
+
+    /// An API serving `config` after every endpoint got a check of `status`.
+    fn api(config: &StatuiConfig, status: CheckStatus) -> Arc<Api> {
+        let api = Api::new();
+        api.set_config(config);
+        let now = SystemTime::now();
+        for endpoint in &config.endpoints {
+            let mut state = EndpointState::new(endpoint, config);
+            state.record(now, result(&endpoint.name, status.clone(), 20), config);
+            api.update(&state, None, now);
+        }
+        let order: Vec<String> = config.endpoints.iter().map(|e| e.name.clone()).collect();
+        api.retain(&order);
+        api
+    }
+
+    async fn body(response: Response) -> (StatusCode, Value) {
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn get_history(api: &Arc<Api>, name: &str, query: Option<&str>) -> (StatusCode, Value) {
+        let query = RawQuery(query.map(str::to_string));
+        body(history(State(api.clone()), Path(name.to_string()), query).await).await
+    }
+
+    #[tokio::test]
+    async fn lists_endpoints_in_config_order() {
+        let mut config = StatuiConfig {
+            endpoints: vec![endpoint("web"), endpoint("api")],
+            ..StatuiConfig::default()
+        };
+        config.history.enabled = false;
+        let timeout = CheckStatus::Error {
+            message: "Timeout".to_string(),
+        };
+
+        let (status, json) = body(endpoints(State(api(&config, timeout))).await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["status"], "down");
+        assert_eq!(json["endpoints"][0]["name"], "web");
+        assert_eq!(json["endpoints"][1]["name"], "api");
+        assert_eq!(json["endpoints"][1]["consecutive_failures"], 1);
+    }
+
+    #[tokio::test]
+    async fn serves_the_checks_in_memory_without_history() {
+        let mut config = StatuiConfig {
+            endpoints: vec![endpoint("api")],
+            ..StatuiConfig::default()
+        };
+        config.history.enabled = false;
+        let api = api(&config, ok());
+
+        let (status, json) = get_history(&api, "api", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["checks"].as_array().unwrap().len(), 1);
+        assert_eq!(json["checks"][0]["code"], 200);
+
+        let (status, json) = get_history(&api, "nope", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(json["error"], "no endpoint named 'nope'");
+    }
+
+    #[tokio::test]
+    async fn reads_the_history_on_disk() {
+        let mut config = StatuiConfig {
+            endpoints: vec![endpoint("api")],
+            ..StatuiConfig::default()
+        };
+        let dir = std::env::temp_dir().join(format!("statui-api-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        config.history.dir = Some(dir.clone());
+
+        let now = SystemTime::now();
+        let mut writer = HistoryWriter::spawn(&config.history).unwrap().unwrap();
+        for (ago, latency_ms) in [(180, 20), (120, 20), (60, 3000)] {
+            let at = now - Duration::from_secs(ago * 60);
+            writer.append(CheckRecord::new(at, &result("api", ok(), latency_ms)));
+        }
+        writer.append(CheckRecord::new(now, &result("web", ok(), 20)));
+        writer.close();
+
+        let api = api(&config, ok());
+        let last_day = get_history(&api, "api", None).await;
+        let last_2h = get_history(&api, "api", Some("since=150m")).await;
+        let bad = get_history(&api, "api", Some("since=soon")).await;
+        let _ = fs::remove_dir_all(&dir);
+
+        let (status, json) = last_day;
+        assert_eq!(status, StatusCode::OK);
+        let checks = json["checks"].as_array().unwrap();
+        assert_eq!(checks.len(), 3);
+        // Newest first, the slow one is degraded like it was in the TUI
+        assert_eq!(checks[0]["latency_ms"], 3000);
+        assert_eq!(checks[0]["status"], "degraded");
+        assert_eq!(checks[2]["status"], "up");
+
+        let (_, json) = last_2h;
+        assert_eq!(json["checks"].as_array().unwrap().len(), 2);
+
+        let (status, json) = bad;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("isn't a duration"));
+    }
+
+    #[test]
+    fn compares_tokens_in_full() {
+        assert!(constant_time_eq("secret", "secret"));
+        assert!(!constant_time_eq("secret", "secreT"));
+        assert!(!constant_time_eq("secret", "secret2"));
+        assert!(!constant_time_eq("", "secret"));
+    }
+
+    fn request(uri: &str, bearer: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(bearer) = bearer {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {bearer}"));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn decodes_the_query_token() {
+        let token = "a+b/c=";
+        assert!(authorized(
+            &request("/api/events?token=a%2Bb%2Fc%3D", None),
+            token
+        ));
+        assert!(authorized(&request("/api/endpoints", Some(token)), token));
+
+        assert!(!authorized(
+            &request("/api/events?token=a+b/c=", None),
+            token
+        ));
+        assert!(!authorized(
+            &request("/api/events?tokens=a%2Bb%2Fc%3D", None),
+            token
+        ));
+        assert!(!authorized(&request("/api/endpoints", None), token));
+    }
+
+    #[test]
+    fn checks_keep_their_assessed_health() {
+        let result = CheckResult {
+            name: "api".to_string(),
+            status: CheckStatus::Success {
+                code: 200,
+                text: "OK".to_string(),
+            },
+            latency: Duration::from_millis(2500),
+            cert_expiry: None,
+            generation: 0,
+        };
+
+        let check = check(SystemTime::UNIX_EPOCH, &result, Health::Degraded);
+        assert_eq!(check["status"], json!(Health::Degraded));
+    }
+}
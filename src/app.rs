@@ -4,17 +4,22 @@ use std::time::{Duration, Instant};
 use ratatui::{
     Terminal,
     backend::Backend,
-    crossterm::event::{self, Event},
+    crossterm::event::{self, Event, KeyCode, KeyEvent},
 };
 
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{Receiver, Sender};
 
 use crate::{
     actions::handle_action,
-    backend::CheckResult,
+    backend::{BackendCommand, CheckResult},
+    config::StatuiConfig,
+    config_edit,
+    form::{FormKind, FormOutcome, PromptOutcome},
     keymap::{self, KeyMap, handle_key_event},
-    state::App,
+    notify::NotifierEvent,
+    state::{App, StatusLevel},
     ui::{self, fx::FxManager},
+    watcher::ConfigEvent,
 };
 
 const FPS: usize = 60;
@@ -22,9 +27,12 @@ const FPS: usize = 60;
 /// TUI entry point that handles drawing the ui, handling input, and displaying
 /// results of a check.
 pub async fn run_app(
-    mut app: &mut App,
+    app: &mut App,
     terminal: &mut Terminal<impl Backend>,
     mut rx: Receiver<CheckResult>,
+    backend_tx: Sender<BackendCommand>,
+    mut config_rx: Receiver<ConfigEvent>,
+    mut notifier_rx: Receiver<NotifierEvent>,
 ) -> Result<()> {
     let mut fx_manager = FxManager::new();
     fx_manager.trigger_startup();
@@ -39,20 +47,25 @@ pub async fn run_app(
 
         // 1. Draw the UI
         terminal.draw(|frame| {
-            ui::render_ui(frame, &mut app, &mut fx_manager, elapsed);
+            ui::render_ui(frame, app, &mut fx_manager, elapsed);
             fx_manager.render_global(frame, frame.area(), elapsed.into());
         })?;
 
         // 2. Handle input
         let km: KeyMap = keymap::default_keymap();
-        if event::poll(Duration::from_millis(1000 / FPS as u64))? {
-            if let Event::Key(key) = event::read()? {
-                let Some(action) = handle_key_event(key, &km) else {
-                    continue;
-                };
-
+        if event::poll(Duration::from_millis(1000 / FPS as u64))?
+            && let Event::Key(key) = event::read()?
+        {
+            // Overlays grab all the input while they're open
+            if app.form.is_some() {
+                on_form_key(app, key, &backend_tx);
+            } else if app.prompt.is_some() {
+                on_prompt_key(app, key);
+            } else if app.pending_delete.is_some() {
+                on_delete_confirm_key(app, key, &backend_tx);
+            } else if let Some(action) = handle_key_event(key, &km) {
                 // Exit if true is returned
-                if handle_action(&action, &mut app, &mut fx_manager) {
+                if handle_action(&action, app, &mut fx_manager, &backend_tx) {
                     return eyre::Ok(());
                 };
             }
@@ -65,5 +78,203 @@ pub async fn run_app(
         while let Ok(result) = rx.try_recv() {
             app.on_result(result);
         }
+
+        // 4. Handle config reloads, and retry the one the backend was
+        // too busy for
+        retry_config_reload(app, &backend_tx);
+        while let Ok(event) = config_rx.try_recv() {
+            on_config_event(app, event, &backend_tx);
+        }
+
+        // 5. Lift the snoozes that ran out
+        app.expire_silences();
+
+        // 6. Report notifications that couldn't be delivered and hooks
+        // that finished running
+        while let Ok(event) = notifier_rx.try_recv() {
+            on_notifier_event(app, event);
+        }
+
+        // 7. Report pushes to the sinks that failed and checks that didn't
+        // make it into the history
+        app.report_sink_failures();
+        app.report_history_drops();
+
+        // 8. Report the exports that finished
+        app.report_exports();
+    }
+}
+
+/// Reports notifications that couldn't be delivered and adds hook runs
+/// to the endpoint logs.
+pub fn on_notifier_event(app: &mut App, event: NotifierEvent) {
+    match event {
+        NotifierEvent::DeliveryFailed(failure) => {
+            let message = format!(
+                "Notification to '{}' about '{}' failed: {}",
+                failure.channel, failure.endpoint, failure.error
+            );
+            app.set_status(StatusLevel::Error, message);
+        }
+        NotifierEvent::HookFinished(outcome) => app.on_hook_finished(outcome),
     }
 }
+
+/// Applies a reloaded config to the app state and the backend workers.
+///
+/// A config that failed to load leaves the running one untouched. Returns
+/// false if the config wasn't applied (yet), the status line says why.
+pub fn on_config_event(
+    app: &mut App,
+    event: ConfigEvent,
+    backend_tx: &Sender<BackendCommand>,
+) -> bool {
+    match event {
+        ConfigEvent::Reloaded(config) => {
+            // The UI can't wait on the backend, a reload that can't be
+            // handed over right away isn't applied yet so the workers
+            // never disagree with the app state. It's retried on the next
+            // tick unless a newer reload replaces it.
+            let Ok(permit) = backend_tx.try_reserve() else {
+                if app.pending_reload.is_none() {
+                    let message = "Config reload delayed, the backend is busy";
+                    app.set_status(StatusLevel::Error, message);
+                }
+                app.pending_reload = Some(config);
+                return false;
+            };
+            app.pending_reload = None;
+
+            let default_interval = config.default_interval;
+            let default_timeout = config.default_timeout;
+            let diff = app.apply_config(*config);
+
+            // Nothing to report, this is usually our own write-back
+            // being picked up by the watcher.
+            if diff.is_empty() {
+                return true;
+            }
+
+            let paused = diff
+                .changed
+                .iter()
+                .filter(|endpoint| {
+                    app.endpoint_states
+                        .get(&endpoint.name)
+                        .is_some_and(|state| state.paused)
+                })
+                .map(|endpoint| endpoint.name.clone())
+                .collect();
+
+            let summary = format!(
+                "Config reloaded: {} added, {} changed, {} removed",
+                diff.added.len(),
+                diff.changed.len(),
+                diff.removed.len()
+            );
+            app.set_status(StatusLevel::Info, summary);
+
+            permit.send(BackendCommand::ApplyDiff {
+                diff,
+                paused,
+                default_interval,
+                default_timeout,
+                generation: app.config_generation,
+            });
+            true
+        }
+        ConfigEvent::Error(err) => {
+            app.set_status(StatusLevel::Error, format!("Config error: {err}"));
+            false
+        }
+    }
+}
+
+/// Applies the reload the backend was too busy to take earlier, if any.
+pub fn retry_config_reload(app: &mut App, backend_tx: &Sender<BackendCommand>) {
+    if let Some(config) = app.pending_reload.take() {
+        on_config_event(app, ConfigEvent::Reloaded(config), backend_tx);
+    }
+}
+
+/// Feeds a key press to the endpoint form and saves the endpoint on submit.
+fn on_form_key(app: &mut App, key: KeyEvent, backend_tx: &Sender<BackendCommand>) {
+    let Some(mut form) = app.form.take() else {
+        return;
+    };
+
+    let endpoint = match form.handle_key(key, app) {
+        FormOutcome::Continue => {
+            app.form = Some(form);
+            return;
+        }
+        FormOutcome::Cancel => return,
+        FormOutcome::Submit(endpoint) => endpoint,
+    };
+
+    let original_name = match &form.kind {
+        FormKind::Add => None,
+        FormKind::Edit { original_name } => Some(original_name.as_str()),
+    };
+
+    match config_edit::upsert_endpoint(&app.config_paths, original_name, &endpoint) {
+        // A reload that failed keeps its error in the status line
+        Ok(path) => {
+            if reload_config(app, backend_tx) {
+                let message = format!("Saved '{}' to {}", endpoint.name, path.display());
+                app.set_status(StatusLevel::Info, message);
+            }
+        }
+        Err(err) => {
+            // Keep the form open so the input isn't lost
+            form.error = Some(format!("Couldn't save: {err}"));
+            app.form = Some(form);
+        }
+    }
+}
+
+/// Feeds a key press to the ack/snooze prompt and silences the endpoint on submit.
+fn on_prompt_key(app: &mut App, key: KeyEvent) {
+    let Some(mut prompt) = app.prompt.take() else {
+        return;
+    };
+
+    match prompt.handle_key(key) {
+        PromptOutcome::Continue => app.prompt = Some(prompt),
+        PromptOutcome::Cancel => {}
+        PromptOutcome::Submit(kind) => app.silence(&prompt.endpoint, kind),
+    }
+}
+
+/// Deletes the pending endpoint on 'y', any other key cancels.
+fn on_delete_confirm_key(app: &mut App, key: KeyEvent, backend_tx: &Sender<BackendCommand>) {
+    let Some(name) = app.pending_delete.take() else {
+        return;
+    };
+
+    if key.code != KeyCode::Char('y') {
+        return;
+    }
+
+    match config_edit::delete_endpoint(&app.config_paths, &name) {
+        Ok(path) => {
+            if reload_config(app, backend_tx) {
+                let message = format!("Deleted '{}' from {}", name, path.display());
+                app.set_status(StatusLevel::Info, message);
+            }
+        }
+        Err(err) => app.set_status(StatusLevel::Error, format!("Couldn't delete: {err}")),
+    }
+}
+
+/// Reloads the config right away after a write-back instead of waiting
+/// for the watcher so the workers start (or stop) immediately.
+///
+/// Returns false if it wasn't applied, see `on_config_event`.
+fn reload_config(app: &mut App, backend_tx: &Sender<BackendCommand>) -> bool {
+    let event = match StatuiConfig::load(&app.config_paths) {
+        Ok(config) => ConfigEvent::Reloaded(Box::new(config)),
+        Err(err) => ConfigEvent::Error(err.to_string()),
+    };
+    on_config_event(app, event, backend_tx)
+}
@@ -1,15 +1,26 @@
-use crate::config::{Endpoint, StatuiConfig};
-use std::time::Duration;
-use tokio::{sync::mpsc::Sender, time::sleep};
+use crate::config::{ConfigDiff, Endpoint, StatuiConfig};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::mpsc::{self, Receiver, Sender},
+    task::JoinHandle,
+    time::sleep,
+};
 
 // This builds the User-Agent string at compile time
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
+const WORKER_COMMAND_BUFFER_SIZE: usize = 8;
+
 /// The status of an HTTP check,
 ///
 /// Either a Success with code and message (e.g. 200 OK)
 /// or Error.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum CheckStatus {
     Success {
         code: u16,
@@ -21,34 +32,208 @@ pub enum CheckStatus {
     },
 }
 
+impl CheckStatus {
+    /// A short class for checks that didn't pass (e.g. "timeout", "http_5xx"),
+    /// None for the ones that did.
+    pub fn error_class(&self) -> Option<&'static str> {
+        match self {
+            CheckStatus::Success { code, .. } if *code >= 500 => Some("http_5xx"),
+            CheckStatus::Success { code, .. } if *code >= 400 => Some("http_4xx"),
+            CheckStatus::Success { .. } => None,
+            CheckStatus::Error { message } => Some(match message.as_str() {
+                "Timeout" => "timeout",
+                "Connection Error" => "connection",
+                _ => "other",
+            }),
+        }
+    }
+}
+
 /// The result of an HTTP check on an endpoint.
 #[derive(Debug, Clone)]
 pub struct CheckResult {
     pub name: String,
     pub status: CheckStatus,
     pub latency: Duration,
+    // When the server's TLS certificate expires, for https endpoints
+    pub cert_expiry: Option<SystemTime>,
+    // The config reload the worker was started by, see `BackendCommand::ApplyDiff`
+    pub generation: u64,
+}
+
+/// A command sent from the UI to the backend.
+///
+/// Commands that carry a name are routed to the worker of that endpoint,
+/// the `*All` variants are broadcast to every worker.
+#[derive(Debug, Clone)]
+pub enum BackendCommand {
+    CheckNow(String),
+    Pause(String),
+    Resume(String),
+    PauseAll,
+    ResumeAll,
+    /// Applies a reloaded config by spawning, stopping and restarting
+    /// only the workers whose endpoints were touched.
+    ///
+    /// The new workers tag their results with `generation` so the UI can
+    /// drop the ones still in flight from the workers they replaced.
+    /// Changed endpoints named in `paused` were paused and stay that way.
+    ApplyDiff {
+        diff: ConfigDiff,
+        paused: Vec<String>,
+        default_interval: u64,
+        default_timeout: u64,
+        generation: u64,
+    },
+}
+
+/// A command understood by a single endpoint_worker.
+#[derive(Debug, Clone, Copy)]
+enum WorkerCommand {
+    CheckNow,
+    Pause,
+    Resume,
+}
+
+/// The handles the backend keeps around for every running worker.
+struct WorkerHandle {
+    cmd_tx: Sender<WorkerCommand>,
+    task: JoinHandle<()>,
 }
 
 /// Backend entry point that spawns N async tasks with endpoint_worker
 /// using tokio where N is the number of Endpoints in StatuiConfig.
-pub async fn run_backend(config: StatuiConfig, tx: Sender<CheckResult>) {
+///
+/// After spawning the workers it keeps running to route the commands
+/// coming from the UI to the right worker until the UI hangs up.
+pub async fn run_backend(
+    config: StatuiConfig,
+    tx: Sender<CheckResult>,
+    mut cmd_rx: Receiver<BackendCommand>,
+) {
+    let mut workers: HashMap<String, WorkerHandle> = HashMap::new();
+
     for endpoint in config.endpoints {
-        let tx_clone = tx.clone();
-        let skip_cert_verification = endpoint.skip_cert_verification.unwrap_or(false);
-        let client = reqwest::Client::builder()
-            .user_agent(APP_USER_AGENT)
-            .danger_accept_invalid_certs(skip_cert_verification)
-            .build()
-            .expect("Failed to build reqwest client");
-        let client_clone = client.clone();
-
-        tokio::spawn(endpoint_worker(
-            endpoint,
+        let worker = spawn_worker(
+            endpoint.clone(),
             config.default_interval,
             config.default_timeout,
-            client_clone,
-            tx_clone,
-        ));
+            0,
+            false,
+            tx.clone(),
+        );
+        workers.insert(endpoint.name, worker);
+    }
+
+    while let Some(command) = cmd_rx.recv().await {
+        match command {
+            BackendCommand::CheckNow(name) => {
+                send_to_worker(&workers, &name, WorkerCommand::CheckNow).await
+            }
+            BackendCommand::Pause(name) => {
+                send_to_worker(&workers, &name, WorkerCommand::Pause).await
+            }
+            BackendCommand::Resume(name) => {
+                send_to_worker(&workers, &name, WorkerCommand::Resume).await
+            }
+            BackendCommand::PauseAll => {
+                for worker in workers.values() {
+                    let _ = worker.cmd_tx.send(WorkerCommand::Pause).await;
+                }
+            }
+            BackendCommand::ResumeAll => {
+                for worker in workers.values() {
+                    let _ = worker.cmd_tx.send(WorkerCommand::Resume).await;
+                }
+            }
+            BackendCommand::ApplyDiff {
+                diff,
+                paused,
+                default_interval,
+                default_timeout,
+                generation,
+            } => {
+                for name in diff.removed {
+                    if let Some(worker) = workers.remove(&name) {
+                        worker.task.abort();
+                    }
+                }
+
+                // Changed endpoints are restarted from scratch since their
+                // url, method or client settings might be different now.
+                for endpoint in diff.added.into_iter().chain(diff.changed) {
+                    if let Some(worker) = workers.remove(&endpoint.name) {
+                        worker.task.abort();
+                    }
+
+                    let worker = spawn_worker(
+                        endpoint.clone(),
+                        default_interval,
+                        default_timeout,
+                        generation,
+                        paused.contains(&endpoint.name),
+                        tx.clone(),
+                    );
+                    workers.insert(endpoint.name, worker);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_worker(
+    endpoint: Endpoint,
+    default_interval: u64,
+    default_timeout: u64,
+    generation: u64,
+    paused: bool,
+    tx: Sender<CheckResult>,
+) -> WorkerHandle {
+    let (cmd_tx, cmd_rx) = mpsc::channel(WORKER_COMMAND_BUFFER_SIZE);
+
+    let task = tokio::spawn(endpoint_worker(
+        endpoint,
+        default_interval,
+        default_timeout,
+        generation,
+        paused,
+        tx,
+        cmd_rx,
+    ));
+
+    WorkerHandle { cmd_tx, task }
+}
+
+fn build_client(endpoint: &Endpoint) -> reqwest::Client {
+    let skip_cert_verification = endpoint.skip_cert_verification.unwrap_or(false);
+    reqwest::Client::builder()
+        .user_agent(APP_USER_AGENT)
+        .danger_accept_invalid_certs(skip_cert_verification)
+        .tls_info(true)
+        .build()
+        .expect("Failed to build reqwest client")
+}
+
+/// Checks the endpoint a single time with the same settings a worker uses.
+///
+/// Unlike the workers there's no warm-up request, the endpoint is only hit
+/// once (which matters for a POST) so the latency includes the connection setup.
+pub async fn check_once(endpoint: &Endpoint, default_timeout: u64) -> CheckResult {
+    let client = build_client(endpoint);
+    let timeout = Duration::from_secs(endpoint.timeout.unwrap_or(default_timeout));
+
+    check_endpoint(endpoint, timeout, &client).await
+}
+
+async fn send_to_worker(
+    workers: &HashMap<String, WorkerHandle>,
+    name: &str,
+    command: WorkerCommand,
+) {
+    // A closed worker channel means the worker already quit,
+    // there's nothing left to route to.
+    if let Some(worker) = workers.get(name) {
+        let _ = worker.cmd_tx.send(command).await;
     }
 }
 
@@ -56,33 +241,65 @@ async fn endpoint_worker(
     endpoint: Endpoint,
     default_interval: u64,
     default_timeout: u64,
-    client: reqwest::Client,
+    generation: u64,
+    mut paused: bool,
     tx: Sender<CheckResult>,
+    mut cmd_rx: Receiver<WorkerCommand>,
 ) {
+    let client = build_client(&endpoint);
+
     let interval = endpoint.interval.unwrap_or(default_interval);
     let interval = Duration::from_secs(interval);
 
     let timeout = endpoint.timeout.unwrap_or(default_timeout);
     let timeout = Duration::from_secs(timeout);
 
-    // Throw-away request for the client to warm up
-    let _ = check_endpoint(&endpoint, timeout, &client).await;
-
-    sleep(Duration::from_millis(50)).await;
+    // A worker that starts paused doesn't touch the endpoint until it's resumed
+    let mut warmed_up = false;
 
     loop {
-        // Perform the actual HTTP check
-        let result = check_endpoint(&endpoint, timeout, &client).await;
+        if !paused {
+            if !warmed_up {
+                // Throw-away request for the client to warm up
+                let _ = check_endpoint(&endpoint, timeout, &client).await;
+                sleep(Duration::from_millis(50)).await;
+                warmed_up = true;
+            }
 
-        // Send the result to the TUI.
-        // If tx.send fails, the TUI has probably quit.
-        // We can safely shut down this worker task by breaking the loop.
-        if tx.send(result).await.is_err() {
-            break;
+            // Perform the actual HTTP check
+            let mut result = check_endpoint(&endpoint, timeout, &client).await;
+            result.generation = generation;
+
+            // Send the result to the TUI.
+            // If tx.send fails, the TUI has probably quit.
+            // We can safely shut down this worker task by breaking the loop.
+            if tx.send(result).await.is_err() {
+                break;
+            }
         }
 
-        // Sleep until the next check
-        sleep(interval).await;
+        // Sleep until the next check or until a command wakes us up.
+        // A paused worker only waits for commands.
+        let command = if paused {
+            cmd_rx.recv().await
+        } else {
+            tokio::select! {
+                _ = sleep(interval) => continue,
+                command = cmd_rx.recv() => command,
+            }
+        };
+
+        let Some(command) = command else {
+            break;
+        };
+
+        match command {
+            // Falling through to the top of the loop runs the check
+            // right away (or does nothing if we're paused).
+            WorkerCommand::CheckNow => {}
+            WorkerCommand::Pause => paused = true,
+            WorkerCommand::Resume => paused = false,
+        }
     }
 }
 
@@ -102,9 +319,14 @@ async fn check_endpoint(
         request = request.header(key, value);
     }
 
-    let (status, latency) = match request.send().await {
+    let (status, latency, cert_expiry) = match request.send().await {
         Ok(response) => {
             let latency = start_time.elapsed();
+            let cert_expiry = response
+                .extensions()
+                .get::<reqwest::tls::TlsInfo>()
+                .and_then(|info| info.peer_certificate())
+                .and_then(cert_expiry);
             let status = CheckStatus::Success {
                 code: response.status().as_u16(),
                 text: response
@@ -113,7 +335,7 @@ async fn check_endpoint(
                     .unwrap_or("Unknown")
                     .to_string(),
             };
-            (status, latency)
+            (status, latency, cert_expiry)
         }
         Err(e) => {
             let latency = start_time.elapsed();
@@ -126,7 +348,7 @@ async fn check_endpoint(
                     String::from("Error")
                 },
             };
-            (status, latency)
+            (status, latency, None)
         }
     };
 
@@ -134,5 +356,14 @@ async fn check_endpoint(
         name: endpoint.name.clone(),
         status,
         latency,
+        cert_expiry,
+        generation: 0,
     }
 }
+
+/// Reads the expiry date out of a DER encoded certificate.
+fn cert_expiry(der: &[u8]) -> Option<SystemTime> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+    let not_after = u64::try_from(cert.validity().not_after.timestamp()).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(not_after))
+}
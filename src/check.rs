@@ -0,0 +1,496 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use color_eyre::{Result, eyre::bail};
+use serde_json::json;
+use tokio::task::JoinSet;
+
+use crate::{
+    backend::{self, CheckResult, CheckStatus},
+    cli::CheckArgs,
+    config::{ConfigPaths, Endpoint, StatuiConfig},
+    state::EndpointState,
+    stats::incidents::Health,
+    ui::util::xml_escape,
+};
+
+// One-shot `statui check`. Every selected endpoint is checked once, all at
+// the same time, and the exit code tells how the worst one did so it can
+// be used as a smoke test in CI.
+
+/// The exit code when the checks couldn't run at all, like Nagios' UNKNOWN.
+pub const EXIT_UNKNOWN: i32 = 3;
+
+// Each endpoint gets a single cold request that also pays for DNS, TCP and
+// TLS, so the global latency thresholds get this much slack. Thresholds set
+// on an endpoint are used as they are.
+const COLD_START_ALLOWANCE_MS: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CheckFormat {
+    Table,
+    Json,
+    /// JUnit XML, one testsuite per tag
+    Junit,
+    /// Test Anything Protocol (version 13)
+    Tap,
+}
+
+/// The outcome of checking a single endpoint.
+pub struct EndpointCheck {
+    pub endpoint: Endpoint,
+    pub health: Health,
+    // Why the endpoint isn't up, or the status of the check when it is
+    pub reason: String,
+    pub result: CheckResult,
+}
+
+/// Runs `statui check` and returns the exit code, 0 if every endpoint is
+/// up, 1 if any is degraded and 2 if any is down.
+pub async fn run(args: &CheckArgs) -> Result<i32> {
+    let config = StatuiConfig::load(&ConfigPaths::new(args.config.clone()))?;
+
+    let endpoints: Vec<Endpoint> = config
+        .endpoints
+        .iter()
+        .filter(|endpoint| {
+            (args.endpoint.is_empty() || args.endpoint.contains(&endpoint.name))
+                && (args.tag.is_empty() || endpoint.tags.iter().any(|t| args.tag.contains(t)))
+        })
+        .cloned()
+        .collect();
+
+    if endpoints.is_empty() {
+        bail!("No endpoints match, check the --endpoint and --tag filters");
+    }
+
+    let started = SystemTime::now();
+    let checks = check_all(&config, endpoints).await;
+
+    match args.format {
+        CheckFormat::Table => print_table(&checks),
+        CheckFormat::Json => print_json(&checks),
+        CheckFormat::Junit => print!("{}", junit(&checks, started)),
+        CheckFormat::Tap => print!("{}", tap(&checks)),
+    }
+
+    Ok(exit_code(&checks))
+}
+
+/// Checks every endpoint concurrently, the results keep the order of `endpoints`.
+///
+/// A check that panicked counts as down.
+pub async fn check_all(config: &StatuiConfig, endpoints: Vec<Endpoint>) -> Vec<EndpointCheck> {
+    let config = &with_cold_start_allowance(config);
+
+    let mut tasks = JoinSet::new();
+    let mut spawned = HashMap::new();
+    for (i, endpoint) in endpoints.into_iter().enumerate() {
+        let default_timeout = config.default_timeout;
+        let checked = endpoint.clone();
+        let handle =
+            tasks.spawn(async move { backend::check_once(&checked, default_timeout).await });
+        spawned.insert(handle.id(), (i, endpoint));
+    }
+
+    let mut checks: Vec<(usize, EndpointCheck)> = Vec::new();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (id, result) = match joined {
+            Ok((id, result)) => (id, Ok(result)),
+            Err(err) => (err.id(), Err(err)),
+        };
+        let Some((i, endpoint)) = spawned.remove(&id) else {
+            continue;
+        };
+
+        let check = match result {
+            Ok(result) => {
+                // Going through the same state as the TUI so slow responses are
+                // degraded the same way.
+                let mut state = EndpointState::new(&endpoint, config);
+                state.record(SystemTime::now(), result.clone(), config);
+
+                EndpointCheck {
+                    health: state.health.unwrap_or(Health::Down),
+                    reason: state.health_reason,
+                    endpoint,
+                    result,
+                }
+            }
+            Err(err) => EndpointCheck {
+                health: Health::Down,
+                reason: format!("Check failed: {err}"),
+                result: CheckResult {
+                    name: endpoint.name.clone(),
+                    status: CheckStatus::Error {
+                        message: "Error".to_string(),
+                    },
+                    latency: Duration::ZERO,
+                    cert_expiry: None,
+                    generation: 0,
+                },
+                endpoint,
+            },
+        };
+        checks.push((i, check));
+    }
+
+    checks.sort_by_key(|(i, _)| *i);
+    checks.into_iter().map(|(_, check)| check).collect()
+}
+
+/// The config with the global latency thresholds loosened for a cold request.
+fn with_cold_start_allowance(config: &StatuiConfig) -> StatuiConfig {
+    let mut config = config.clone();
+    config.latency_warn_ms = config
+        .latency_warn_ms
+        .saturating_add(COLD_START_ALLOWANCE_MS);
+    config.latency_critical_ms = config
+        .latency_critical_ms
+        .saturating_add(COLD_START_ALLOWANCE_MS);
+    config
+}
+
+impl EndpointCheck {
+    fn code(&self) -> Option<u16> {
+        match self.result.status {
+            CheckStatus::Success { code, .. } => Some(code),
+            CheckStatus::Error { .. } => None,
+        }
+    }
+
+    // Degraded endpoints fail the check as well (they make it exit 1), a
+    // slow response has no error class so it's reported as "degraded".
+    fn failure_type(&self) -> &'static str {
+        self.result.status.error_class().unwrap_or("degraded")
+    }
+}
+
+pub fn exit_code(checks: &[EndpointCheck]) -> i32 {
+    match checks.iter().map(|check| check.health).max() {
+        Some(Health::Down) => 2,
+        Some(Health::Degraded) => 1,
+        _ => 0,
+    }
+}
+
+fn print_table(checks: &[EndpointCheck]) {
+    let header = ["ENDPOINT", "STATUS", "CHECK", "LATENCY"];
+    let rows: Vec<[String; 4]> = checks
+        .iter()
+        .map(|check| {
+            [
+                check.endpoint.name.clone(),
+                check.health.label().to_string(),
+                check.reason.clone(),
+                format!("{}ms", check.result.latency.as_millis()),
+            ]
+        })
+        .collect();
+
+    let width = |col: usize| {
+        rows.iter()
+            .map(|row| row[col].chars().count())
+            .chain([header[col].len()])
+            .max()
+            .unwrap_or(0)
+    };
+    let widths = [width(0), width(1), width(2)];
+
+    println!(
+        "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+        header[0],
+        header[1],
+        header[2],
+        header[3],
+        w0 = widths[0],
+        w1 = widths[1],
+        w2 = widths[2]
+    );
+    for row in &rows {
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+    }
+
+    let count = |health: Health| checks.iter().filter(|c| c.health == health).count();
+    println!(
+        "\n{} up, {} degraded, {} down",
+        count(Health::Up),
+        count(Health::Degraded),
+        count(Health::Down)
+    );
+}
+
+fn print_json(checks: &[EndpointCheck]) {
+    let status = checks
+        .iter()
+        .map(|check| check.health)
+        .max()
+        .unwrap_or(Health::Up);
+
+    let endpoints: Vec<serde_json::Value> = checks
+        .iter()
+        .map(|check| {
+            json!({
+                "endpoint": check.endpoint.name,
+                "url": check.endpoint.url,
+                "tags": check.endpoint.tags,
+                "status": check.health,
+                "code": check.code(),
+                "error_class": check.result.status.error_class(),
+                "message": check.reason,
+                "latency_ms": check.result.latency.as_millis() as u64,
+            })
+        })
+        .collect();
+
+    let report = json!({
+        "status": status,
+        "endpoints": endpoints,
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    );
+}
+
+// Endpoints without tags go in a testsuite of their own
+const UNTAGGED_SUITE: &str = "untagged";
+
+/// Renders the checks as JUnit XML. Every endpoint is a testcase in the
+/// testsuite of each of its tags, with the latency as its time.
+fn junit(checks: &[EndpointCheck], started: SystemTime) -> String {
+    let mut suites: BTreeMap<&str, Vec<&EndpointCheck>> = BTreeMap::new();
+    for check in checks {
+        if check.endpoint.tags.is_empty() {
+            suites.entry(UNTAGGED_SUITE).or_default().push(check);
+        }
+        for tag in &check.endpoint.tags {
+            suites.entry(tag).or_default().push(check);
+        }
+    }
+
+    let seconds = |checks: &[&EndpointCheck]| -> f64 {
+        checks
+            .iter()
+            .map(|check| check.result.latency.as_secs_f64())
+            .sum()
+    };
+    let failures =
+        |checks: &[&EndpointCheck]| checks.iter().filter(|c| c.health != Health::Up).count();
+
+    let timestamp: DateTime<Utc> = started.into();
+    let timestamp = timestamp.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let all: Vec<&EndpointCheck> = suites.values().flatten().copied().collect();
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        out,
+        r#"<testsuites name="statui" tests="{}" failures="{}" errors="0" time="{:.3}">"#,
+        all.len(),
+        failures(&all),
+        seconds(&all)
+    );
+
+    for (tag, checks) in &suites {
+        let _ = writeln!(
+            out,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0" time="{:.3}" timestamp="{timestamp}">"#,
+            xml_escape(tag),
+            checks.len(),
+            failures(checks),
+            seconds(checks)
+        );
+
+        for check in checks {
+            let _ = write!(
+                out,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+                xml_escape(&check.endpoint.name),
+                xml_escape(tag),
+                check.result.latency.as_secs_f64()
+            );
+            if check.health == Health::Up {
+                let _ = writeln!(out, "/>");
+                continue;
+            }
+
+            let method = check.endpoint.method.as_deref().unwrap_or("GET");
+            let _ = writeln!(out, ">");
+            let _ = writeln!(
+                out,
+                r#"      <failure type="{}" message="{}">{} {} is {}: {}</failure>"#,
+                check.failure_type(),
+                xml_escape(&check.reason),
+                xml_escape(method),
+                xml_escape(&check.endpoint.url),
+                check.health.label().to_lowercase(),
+                xml_escape(&check.reason)
+            );
+            let _ = writeln!(out, "    </testcase>");
+        }
+
+        let _ = writeln!(out, "  </testsuite>");
+    }
+
+    let _ = writeln!(out, "</testsuites>");
+    out
+}
+
+/// Renders the checks in the Test Anything Protocol, the details of each
+/// failing endpoint are attached as a YAML block.
+fn tap(checks: &[EndpointCheck]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "TAP version 13");
+    let _ = writeln!(out, "1..{}", checks.len());
+
+    for (i, check) in checks.iter().enumerate() {
+        // A '#' would start a TAP directive
+        let name = check.endpoint.name.replace('#', "\\#");
+        if check.health == Health::Up {
+            let _ = writeln!(out, "ok {} - {name}", i + 1);
+            continue;
+        }
+
+        let _ = writeln!(out, "not ok {} - {name}", i + 1);
+        let _ = writeln!(out, "  ---");
+        let _ = writeln!(out, "  url: {}", yaml_string(&check.endpoint.url));
+        let _ = writeln!(out, "  status: {}", check.health.label().to_lowercase());
+        if let Some(code) = check.code() {
+            let _ = writeln!(out, "  code: {code}");
+        }
+        let _ = writeln!(out, "  error_class: {}", check.failure_type());
+        let _ = writeln!(out, "  message: {}", yaml_string(&check.reason));
+        let _ = writeln!(out, "  latency_ms: {}", check.result.latency.as_millis());
+        let _ = writeln!(out, "  ...");
+    }
+
+    out
+}
+
+/// Quotes a YAML scalar, JSON strings are valid YAML.
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn check(name: &str, tags: &[&str], health: Health, status: CheckStatus) -> EndpointCheck {
+        let endpoint = Endpoint {
+            name: name.to_string(),
+            url: format!("https://{name}.example.com"),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Endpoint::default()
+        };
+        let reason = match &status {
+            CheckStatus::Success { code, text } => format!("{code} {text}"),
+            CheckStatus::Error { message } => message.clone(),
+        };
+        EndpointCheck {
+            result: CheckResult {
+                name: endpoint.name.clone(),
+                status,
+                latency: Duration::from_millis(250),
+                cert_expiry: None,
+                generation: 0,
+            },
+            endpoint,
+            health,
+            reason,
+        }
+    }
+
+    fn checks() -> Vec<EndpointCheck> {
+        let ok = CheckStatus::Success {
+            code: 200,
+            text: "OK".to_string(),
+        };
+        let timeout = CheckStatus::Error {
+            message: "Timeout".to_string(),
+        };
+        vec![
+            check("api", &["prod", "eu"], Health::Up, ok),
+            check("db #1", &[], Health::Down, timeout),
+        ]
+    }
+
+    #[test]
+    fn exits_with_the_worst_health() {
+        let mut checks = checks();
+        assert_eq!(exit_code(&checks), 2);
+        checks.pop();
+        assert_eq!(exit_code(&checks), 0);
+        assert_eq!(exit_code(&[]), 0);
+    }
+
+    #[test]
+    fn renders_junit() {
+        let xml = junit(&checks(), UNIX_EPOCH);
+        assert!(xml.contains(
+            r#"<testsuites name="statui" tests="3" failures="1" errors="0" time="0.750">"#
+        ));
+        // Suites are sorted by tag, the endpoint shows up under both of its tags
+        let suites: Vec<&str> = xml
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(r#"<testsuite name=""#))
+            .map(|line| line.split('"').next().unwrap_or_default())
+            .collect();
+        assert_eq!(suites, ["eu", "prod", "untagged"]);
+        assert!(xml.contains(r#"<testcase name="api" classname="eu" time="0.250"/>"#));
+        assert!(xml.contains(
+            r#"<failure type="timeout" message="Timeout">GET https://db #1.example.com is down: Timeout</failure>"#
+        ));
+        assert!(xml.contains(r#"timestamp="1970-01-01T00:00:00Z""#));
+    }
+
+    #[test]
+    fn renders_tap() {
+        assert_eq!(
+            tap(&checks()),
+            "TAP version 13\n\
+             1..2\n\
+             ok 1 - api\n\
+             not ok 2 - db \\#1\n  \
+             ---\n  \
+             url: \"https://db #1.example.com\"\n  \
+             status: down\n  \
+             error_class: timeout\n  \
+             message: \"Timeout\"\n  \
+             latency_ms: 250\n  \
+             ...\n"
+        );
+    }
+
+    #[test]
+    fn loosens_only_the_global_thresholds() {
+        let config = with_cold_start_allowance(&StatuiConfig::default());
+        let global = Endpoint::default();
+        let own = Endpoint {
+            latency_warn_ms: Some(100),
+            latency_critical_ms: Some(300),
+            ..Endpoint::default()
+        };
+
+        let thresholds = config.latency_thresholds(&global);
+        assert_eq!((thresholds.warn_ms, thresholds.critical_ms), (700, 1000));
+        let thresholds = config.latency_thresholds(&own);
+        assert_eq!((thresholds.warn_ms, thresholds.critical_ms), (100, 300));
+    }
+}
@@ -0,0 +1,77 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::{check::CheckFormat, config, export::ExportFormat};
+
+/// A lightweight TUI dashboard for API health monitoring.
+#[derive(Debug, Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    /// The config file to use, statui.toml by default
+    pub config: Option<PathBuf>,
+
+    /// Run without the TUI and log to stdout as JSON lines
+    #[arg(long)]
+    pub headless: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write the check history to stdout
+    Export(ExportArgs),
+    /// Check the endpoints once and exit 0, 1 or 2 if all are up, any is degraded or any is down
+    Check(CheckArgs),
+    /// Write a static HTML status page built from the check history
+    StatusPage(StatusPageArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// The config file to use, statui.toml by default
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Csv)]
+    pub format: ExportFormat,
+
+    /// How far back to go, like 30m, 24h or 7d
+    #[arg(short, long, default_value = "24h", value_parser = config::parse_duration)]
+    pub since: Duration,
+
+    /// Only export the checks of this endpoint
+    #[arg(short, long)]
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// The config file to use, statui.toml by default
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Only check this endpoint, can be repeated
+    #[arg(short, long, conflicts_with = "tag")]
+    pub endpoint: Vec<String>,
+
+    /// Only check the endpoints with this tag, can be repeated
+    #[arg(short, long)]
+    pub tag: Vec<String>,
+
+    #[arg(short, long, value_enum, default_value_t = CheckFormat::Table)]
+    pub format: CheckFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct StatusPageArgs {
+    /// The config file to use, statui.toml by default
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// The directory to write index.html to, `out` in [status_page] by default
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+}
@@ -1,18 +1,22 @@
-use color_eyre::Result;
+use color_eyre::{Result, eyre::bail};
 use config::{Config, File};
 use directories::ProjectDirs;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
 use std::collections::HashMap;
-use std::path::Path;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
-const APP_QUALIFIER: &str = "com";
-const APP_ORGANIZATION: &str = "statui";
-const APP_NAME: &str = "statui";
+use crate::stats::{incidents::Health, uptime::UptimeWindow};
+
+pub const APP_QUALIFIER: &str = "com";
+pub const APP_ORGANIZATION: &str = "statui";
+pub const APP_NAME: &str = "statui";
 
 /// The configuration for a single endpoint.
 ///
 /// This maps directly to the `[[endpoints]]` block in statui.toml.
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
 pub struct Endpoint {
     pub name: String,
     pub url: String,
@@ -22,6 +26,20 @@ pub struct Endpoint {
     pub timeout: Option<u64>,
     pub method: Option<String>,
     pub skip_cert_verification: Option<bool>,
+    pub latency_warn_ms: Option<u64>,
+    pub latency_critical_ms: Option<u64>,
+    pub slo: Option<SloConfig>,
+
+    // Name of the `[groups.<name>]` block this endpoint belongs to
+    pub group: Option<String>,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    // Commands run when the endpoint changes state, see `HooksConfig`
+    pub on_down: Option<String>,
+    pub on_up: Option<String>,
+    pub on_degraded: Option<String>,
 
     #[serde(default)]
     pub headers: HashMap<String, String>,
@@ -36,48 +54,824 @@ pub struct StatuiConfig {
     pub default_interval: u64,
     pub default_timeout: u64,
 
+    // Latencies above these are shown as slow/critical, critical ones also
+    // degrade the endpoint. Endpoints can override them.
+    pub latency_warn_ms: u64,
+    pub latency_critical_ms: u64,
+
+    // The uptime window shown in the table (1h, 24h, 7d or 30d)
+    pub uptime_window: UptimeWindow,
+
+    pub history: HistoryConfig,
+
+    pub flap_detection: FlapConfig,
+
+    pub anomaly_detection: AnomalyConfig,
+
+    // Settings shared by the endpoints of a group, by group name
+    pub groups: HashMap<String, GroupConfig>,
+
+    pub alerts: Vec<AlertRule>,
+
+    pub notifications: Vec<NotificationConfig>,
+
+    pub hooks: HooksConfig,
+
+    pub metrics: MetricsConfig,
+
+    pub api: ApiConfig,
+
+    pub status_page: StatusPageConfig,
+
+    pub otlp: OtlpConfig,
+
+    pub sinks: Vec<SinkConfig>,
+
     #[serde(default)]
     pub endpoints: Vec<Endpoint>,
 }
 
+/// Settings for the persistent check history.
+///
+/// This maps to the `[history]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub enabled: bool,
+
+    // Segments older than this are deleted
+    pub retention_days: u64,
+
+    // How much of the history is loaded back on startup
+    pub load_days: u64,
+
+    // Defaults to the "history" folder in the app data dir
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            // Enough for the 90 days of the status page
+            retention_days: 90,
+            load_days: 30,
+            dir: None,
+        }
+    }
+}
+
+/// Settings for the Prometheus exporter.
+///
+/// This maps to the `[metrics]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+
+    // Address the /metrics endpoint is served on
+    pub listen: SocketAddr,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: SocketAddr::from(([127, 0, 0, 1], 9898)),
+        }
+    }
+}
+
+/// Settings for detecting endpoints that flap between up and down.
+///
+/// This maps to the `[flap_detection]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct FlapConfig {
+    pub enabled: bool,
+
+    // Number of recent checks the state change rate is computed over
+    pub window: usize,
+
+    // State change percentages to start and stop flapping at
+    pub low_threshold: f64,
+    pub high_threshold: f64,
+}
+
+impl Default for FlapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 21,
+            low_threshold: 20.0,
+            high_threshold: 30.0,
+        }
+    }
+}
+
+/// Settings for flagging latencies that stray from an endpoint's usual latency.
+///
+/// This maps to the `[anomaly_detection]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+
+    // Weight of the newest sample in the baseline (0-1), higher adapts faster
+    pub alpha: f64,
+
+    // How many standard deviations slower than the baseline counts as an anomaly
+    pub threshold: f64,
+
+    // Number of samples to learn from before flagging anything
+    pub warmup: u64,
+
+    // Lower bound on the standard deviation so very stable endpoints
+    // aren't flagged for a couple of milliseconds of jitter
+    pub min_stddev_ms: f64,
+
+    // Whether anomalies mark the endpoint as degraded
+    pub degrade_health: bool,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            alpha: 0.1,
+            threshold: 3.0,
+            warmup: 20,
+            min_stddev_ms: 5.0,
+            degrade_health: true,
+        }
+    }
+}
+
+/// An availability objective, written as `slo = { target = 99.9, window = "30d" }`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct SloConfig {
+    // Percentage of the time the endpoint should be up, below 100 so
+    // there's an error budget to track
+    #[serde(deserialize_with = "deserialize_slo_target")]
+    pub target: f64,
+
+    // The window the objective is measured over (1h, 24h, 7d or 30d)
+    #[serde(default = "default_slo_window")]
+    pub window: UptimeWindow,
+
+    // Burn rate over both the 1h and 6h windows that counts as a fast burn
+    #[serde(default = "default_fast_burn")]
+    pub fast_burn: f64,
+}
+
+fn default_slo_window() -> UptimeWindow {
+    UptimeWindow::Month
+}
+
+fn deserialize_slo_target<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let target = f64::deserialize(deserializer)?;
+    if target > 0.0 && target < 100.0 {
+        Ok(target)
+    } else {
+        Err(D::Error::custom(format!(
+            "SLO target must be above 0 and below 100, got {target}"
+        )))
+    }
+}
+
+/// Settings for the read-only JSON API.
+///
+/// This maps to the `[api]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+
+    // Address the /api routes are served on
+    pub listen: SocketAddr,
+
+    // Required as a bearer token when set, can reference environment variables
+    pub token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: SocketAddr::from(([127, 0, 0, 1], 9899)),
+            token: None,
+        }
+    }
+}
+
+/// Settings for the OpenTelemetry exporter.
+///
+/// This maps to the `[otlp]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OtlpConfig {
+    pub enabled: bool,
+
+    pub protocol: OtlpProtocol,
+
+    // The collector, localhost:4317 for gRPC and localhost:4318 for HTTP by default
+    pub endpoint: Option<String>,
+
+    // Sent with every export, e.g. an API key. ${VAR} is replaced with
+    // the environment variable
+    pub headers: HashMap<String, String>,
+
+    // Describe this statui instance, service.name is "statui" unless it's set here
+    pub resource_attributes: HashMap<String, String>,
+
+    pub traces: bool,
+    pub metrics: bool,
+
+    // Seconds between metric exports
+    pub export_interval: u64,
+
+    // Seconds before an export is given up on
+    pub timeout: u64,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protocol: OtlpProtocol::Grpc,
+            endpoint: None,
+            headers: HashMap::new(),
+            resource_attributes: HashMap::new(),
+            traces: true,
+            metrics: true,
+            export_interval: 60,
+            timeout: 10,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpProtocol {
+    Grpc,
+    Http,
+}
+
+/// A time series database the result of every check is pushed to.
+///
+/// This maps to a `[[sinks]]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SinkConfig {
+    pub name: String,
+    pub kind: SinkKind,
+
+    // Defaults to http for InfluxDB, udp for StatsD and tcp for Graphite
+    pub transport: Option<SinkTransport>,
+
+    // Where http sinks POST to, e.g. the /api/v2/write url of InfluxDB
+    pub url: Option<String>,
+    // host:port that udp and tcp sinks send to
+    pub address: Option<String>,
+
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    // The InfluxDB measurement, the prefix of the StatsD and Graphite metrics
+    #[serde(default = "default_measurement")]
+    pub measurement: String,
+
+    // The endpoint metadata sent as tags. Defaults to the endpoint, group and
+    // tags for InfluxDB and no tags for StatsD and Graphite, which keep the
+    // endpoint in the metric name.
+    pub tags: Option<Vec<SinkTag>>,
+
+    // Lines are pushed once this many are waiting, or every flush_interval seconds
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_flush_interval")]
+    pub flush_interval: u64,
+}
+
+fn default_measurement() -> String {
+    "statui".to_string()
+}
+
+fn default_batch_size() -> usize {
+    500
+}
+
+fn default_flush_interval() -> u64 {
+    10
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    /// InfluxDB line protocol.
+    Influx,
+    /// StatsD gauges and timers, with DogStatsD tags.
+    Statsd,
+    /// Graphite plaintext, with Graphite 1.1 tags.
+    Graphite,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkTransport {
+    Http,
+    Udp,
+    Tcp,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkTag {
+    Endpoint,
+    Url,
+    Method,
+    Group,
+    Tags,
+}
+
+/// Settings for the static status page.
+///
+/// This maps to the `[status_page]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct StatusPageConfig {
+    pub title: String,
+
+    // Days of uptime bars shown per endpoint, can't be more than the
+    // history's retention_days
+    pub days: u64,
+
+    // How many of the most recent incidents are listed
+    pub incidents: usize,
+
+    // An HTML file with {{placeholders}} to use instead of the built-in template
+    pub template: Option<PathBuf>,
+
+    // Headless mode keeps the page in this directory up to date
+    pub out: Option<PathBuf>,
+
+    // Seconds between updates in headless mode
+    pub interval: u64,
+}
+
+impl Default for StatusPageConfig {
+    fn default() -> Self {
+        Self {
+            title: "Status".to_string(),
+            days: 90,
+            incidents: 10,
+            template: None,
+            out: None,
+            interval: 60,
+        }
+    }
+}
+
+// Burning 2% of a 30 day budget in an hour
+fn default_fast_burn() -> f64 {
+    14.4
+}
+
+/// Settings shared by a group of endpoints.
+///
+/// This maps to a `[groups.<name>]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct GroupConfig {
+    pub slo: Option<SloConfig>,
+}
+
+/// An alert rule.
+///
+/// This maps to an `[[alerts]]` block in statui.toml. A rule applies to the
+/// endpoints listed by name or tag, or to every endpoint if neither is given.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    pub condition: AlertCondition,
+
+    // Seconds the condition has to hold before the alert fires,
+    // until then the alert is pending.
+    #[serde(default, rename = "for")]
+    pub for_secs: u64,
+}
+
+impl AlertRule {
+    pub fn applies_to(&self, name: &str, tags: &[String]) -> bool {
+        if self.endpoints.is_empty() && self.tags.is_empty() {
+            return true;
+        }
+
+        self.endpoints.iter().any(|e| e == name) || self.tags.iter().any(|t| tags.contains(t))
+    }
+}
+
+/// What an alert rule watches for,
+/// written as `condition = { type = "consecutive_failures", count = 3 }`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// The last `count` checks all failed.
+    ConsecutiveFailures { count: u64 },
+    /// The P95 of the recent checks is above `ms`.
+    P95Above { ms: u64 },
+    /// The uptime over `window` is below `percent`.
+    UptimeBelow { percent: f64, window: UptimeWindow },
+    /// The TLS certificate expires within `days`.
+    CertExpiring { days: u64 },
+    /// The endpoint is at least as unhealthy as `state`.
+    Health { state: Health },
+    /// The endpoint is burning its SLO error budget too fast.
+    FastBurn,
+}
+
+/// A channel notified when an endpoint changes state.
+///
+/// This maps to a `[[notifications]]` block in statui.toml.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct NotificationConfig {
+    pub name: String,
+    pub kind: NotificationKind,
+    pub url: Option<String>,
+
+    // Message with {{placeholders}}, the whole body for generic webhooks
+    pub template: Option<String>,
+
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    // The states to notify about, all of them if empty
+    #[serde(default)]
+    pub on: Vec<Health>,
+
+    // Attempts after the first failed one, with exponential backoff
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+
+    // -- Email only --
+    pub smtp: Option<SmtpConfig>,
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Vec<String>,
+
+    // Transitions within this many seconds are sent together as one digest
+    #[serde(default = "default_batch_secs")]
+    pub batch_secs: u64,
+}
+
+fn default_retries() -> u32 {
+    5
+}
+
+fn default_batch_secs() -> u64 {
+    30
+}
+
+/// The SMTP server email notifications are sent through.
+///
+/// The username and password can reference environment variables
+/// like `password = "${SMTP_PASSWORD}"`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SmtpConfig {
+    pub host: String,
+    // Defaults to the usual port for the tls mode
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Upgrades a plain connection (port 587).
+    #[default]
+    Starttls,
+    /// TLS from the start (port 465).
+    Tls,
+    /// No encryption at all, only meant for local relays.
+    None,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// POSTs a JSON payload (or the rendered template) to any URL.
+    Webhook,
+    Slack,
+    Discord,
+    Ntfy,
+    Gotify,
+    Email,
+}
+
+/// Commands run when an endpoint changes state.
+///
+/// This maps to the `[hooks]` block in statui.toml, endpoints can set
+/// their own `on_down`/`on_up`/`on_degraded` to override these.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub on_down: Option<String>,
+    pub on_up: Option<String>,
+    pub on_degraded: Option<String>,
+
+    // Seconds before a hook is killed
+    pub timeout: u64,
+
+    // How many hooks can run at the same time, the others wait their turn
+    pub max_concurrent: usize,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_down: None,
+            on_up: None,
+            on_degraded: None,
+            timeout: 30,
+            max_concurrent: 4,
+        }
+    }
+}
+
 /// Default configuration for all endpoints
 impl Default for StatuiConfig {
     fn default() -> Self {
         Self {
             default_interval: 60,
             default_timeout: 5,
+            latency_warn_ms: 200,
+            latency_critical_ms: 500,
+            uptime_window: UptimeWindow::Day,
+            history: HistoryConfig::default(),
+            flap_detection: FlapConfig::default(),
+            anomaly_detection: AnomalyConfig::default(),
+            groups: HashMap::new(),
+            alerts: Vec::new(),
+            notifications: Vec::new(),
+            hooks: HooksConfig::default(),
+            metrics: MetricsConfig::default(),
+            status_page: StatusPageConfig::default(),
+            api: ApiConfig::default(),
+            otlp: OtlpConfig::default(),
+            sinks: Vec::new(),
             endpoints: Vec::new(),
         }
     }
 }
 
+/// The config files statui reads from, in the order they are merged.
+#[derive(Debug, Clone)]
+pub struct ConfigPaths {
+    pub global: Option<PathBuf>,
+    pub local: PathBuf,
+}
+
+impl ConfigPaths {
+    /// Resolves the global app config (in ~/.config/statui/config.toml for linux or
+    /// wherever it is for other systems), and the local config at `local`
+    /// (statui.toml is used by default).
+    pub fn new(local: Option<PathBuf>) -> ConfigPaths {
+        let local_config_path = local.unwrap_or_else(|| PathBuf::from("statui.toml"));
+
+        let global = ProjectDirs::from(APP_QUALIFIER, APP_ORGANIZATION, APP_NAME)
+            .map(|proj_dirs| proj_dirs.config_dir().join("config.toml"));
+
+        ConfigPaths {
+            global,
+            local: local_config_path,
+        }
+    }
+
+    /// Returns all the paths that make up the config, global first.
+    pub fn all(&self) -> Vec<&PathBuf> {
+        self.global.iter().chain([&self.local]).collect()
+    }
+}
+
+/// The latencies at which an endpoint counts as slow and critically slow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyThresholds {
+    pub warn_ms: u64,
+    pub critical_ms: u64,
+}
+
+impl LatencyThresholds {
+    pub fn is_warn(&self, latency_ms: u64) -> bool {
+        latency_ms > self.warn_ms
+    }
+
+    pub fn is_critical(&self, latency_ms: u64) -> bool {
+        latency_ms > self.critical_ms
+    }
+}
+
 impl StatuiConfig {
-    /// Function to build a Configuration by combining the default config,
-    /// the global app config (in ~/.config/statui/config.toml for linux or wherever
-    /// it is for other systems), and the config at the path passed in the first argument
-    /// (if no arguments were passed statui.toml is used by default).
-    pub fn build(mut args: impl Iterator<Item = String>) -> Result<StatuiConfig> {
-        args.next();
-        let local_config_path: String = match args.next() {
-            Some(arg) => arg,
-            None => "statui.toml".to_string(),
+    /// The latency thresholds of `endpoint`, falling back to the global ones.
+    pub fn latency_thresholds(&self, endpoint: &Endpoint) -> LatencyThresholds {
+        LatencyThresholds {
+            warn_ms: endpoint.latency_warn_ms.unwrap_or(self.latency_warn_ms),
+            critical_ms: endpoint
+                .latency_critical_ms
+                .unwrap_or(self.latency_critical_ms),
+        }
+    }
+
+    /// The SLO of `endpoint`, falling back to the one of its group.
+    pub fn slo(&self, endpoint: &Endpoint) -> Option<SloConfig> {
+        endpoint.slo.or_else(|| {
+            let group = self.groups.get(endpoint.group.as_deref()?)?;
+            group.slo
+        })
+    }
+
+    /// The group whose SLO `endpoint` shares, if it has no SLO of its own.
+    pub fn slo_group<'a>(&self, endpoint: &'a Endpoint) -> Option<&'a str> {
+        let name = endpoint.group.as_deref()?;
+        let group = self.groups.get(name)?;
+        (endpoint.slo.is_none() && group.slo.is_some()).then_some(name)
+    }
+
+    /// The hook command to run when `endpoint` becomes `health`,
+    /// falling back to the global one.
+    pub fn hook(&self, endpoint: &Endpoint, health: Health) -> Option<String> {
+        let (own, global) = match health {
+            Health::Down => (&endpoint.on_down, &self.hooks.on_down),
+            Health::Up => (&endpoint.on_up, &self.hooks.on_up),
+            Health::Degraded => (&endpoint.on_degraded, &self.hooks.on_degraded),
         };
+        own.as_ref().or(global.as_ref()).cloned()
+    }
 
+    /// Function to build a Configuration by combining the default config,
+    /// the global app config and the local config (in that order).
+    ///
+    /// See `ConfigPaths::new` for how the paths are resolved.
+    pub fn load(paths: &ConfigPaths) -> Result<StatuiConfig> {
         // default config
         let mut builder = Config::builder().add_source(Config::try_from(&StatuiConfig::default())?);
 
-        if let Some(proj_dirs) = ProjectDirs::from(APP_QUALIFIER, APP_ORGANIZATION, APP_NAME) {
-            // merge global config
-            let global_config_path = proj_dirs.config_dir().join("config.toml");
-            builder = builder.add_source(File::from(global_config_path).required(false));
+        // merge global config
+        if let Some(global_config_path) = &paths.global {
+            builder = builder.add_source(File::from(global_config_path.as_path()).required(false));
         }
 
         // merge local config
-        builder = builder.add_source(File::from(Path::new(&local_config_path)).required(false));
+        builder = builder.add_source(File::from(paths.local.as_path()).required(false));
 
         let config = builder.build()?.try_deserialize::<StatuiConfig>()?;
+        config.validate()?;
         Ok(config)
     }
+
+    /// Checks what can't be checked one field at a time while deserializing.
+    pub fn validate(&self) -> Result<()> {
+        if self.latency_warn_ms > self.latency_critical_ms {
+            bail!(
+                "latency_warn_ms ({}) can't be above latency_critical_ms ({})",
+                self.latency_warn_ms,
+                self.latency_critical_ms
+            );
+        }
+
+        // An endpoint can override either threshold, check what it ends up with
+        for endpoint in &self.endpoints {
+            let thresholds = self.latency_thresholds(endpoint);
+            if thresholds.warn_ms > thresholds.critical_ms {
+                bail!(
+                    "'{}' warns at {}ms but is only critical at {}ms, latency_warn_ms can't be above latency_critical_ms",
+                    endpoint.name,
+                    thresholds.warn_ms,
+                    thresholds.critical_ms
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The difference between two configs in terms of endpoints.
+///
+/// An endpoint is matched across configs by its name, and it counts as
+/// changed if the way it's checked changed (url, method, headers, TLS,
+/// interval or timeout, including the defaults it inherits). Everything
+/// else is picked up by `EndpointState::apply_settings` without a restart.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigDiff {
+    pub added: Vec<Endpoint>,
+    pub changed: Vec<Endpoint>,
+    pub removed: Vec<String>,
+}
+
+impl ConfigDiff {
+    pub fn between(old: &StatuiConfig, new: &StatuiConfig) -> ConfigDiff {
+        let mut diff = ConfigDiff::default();
+
+        for endpoint in &new.endpoints {
+            match old.endpoints.iter().find(|e| e.name == endpoint.name) {
+                None => diff.added.push(endpoint.clone()),
+                Some(old_endpoint) => {
+                    if probe_changed(old, old_endpoint, new, endpoint) {
+                        diff.changed.push(endpoint.clone());
+                    }
+                }
+            }
+        }
+
+        for endpoint in &old.endpoints {
+            if !new.endpoints.iter().any(|e| e.name == endpoint.name) {
+                diff.removed.push(endpoint.name.clone());
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Whether the worker of the endpoint has to be restarted to pick up the new config.
+fn probe_changed(
+    old: &StatuiConfig,
+    old_endpoint: &Endpoint,
+    new: &StatuiConfig,
+    endpoint: &Endpoint,
+) -> bool {
+    old_endpoint.url != endpoint.url
+        || old_endpoint.method != endpoint.method
+        || old_endpoint.headers != endpoint.headers
+        || old_endpoint.skip_cert_verification != endpoint.skip_cert_verification
+        || old_endpoint.interval.unwrap_or(old.default_interval)
+            != endpoint.interval.unwrap_or(new.default_interval)
+        || old_endpoint.timeout.unwrap_or(old.default_timeout)
+            != endpoint.timeout.unwrap_or(new.default_timeout)
+}
+
+/// Replaces `${NAME}` in a config value with the environment variable NAME
+/// so secrets don't have to be written into the config file.
+pub fn interpolate_env(value: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+
+        let name = &rest[start + 2..start + len];
+        let var =
+            std::env::var(name).map_err(|_| format!("environment variable '{name}' isn't set"))?;
+
+        result.push_str(&rest[..start]);
+        result.push_str(&var);
+        rest = &rest[start + len + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Parses a duration like `30m`, `2h` or `1d`, plain numbers are minutes.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: u64 = match number.parse() {
+        Ok(0) | Err(_) => {
+            return Err(format!(
+                "'{value}' isn't a duration, try something like 30m, 2h or 1d"
+            ));
+        }
+        Ok(number) => number,
+    };
+
+    let seconds = match unit.trim() {
+        "s" => 1,
+        "" | "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        other => return Err(format!("Unknown unit '{other}', use s, m, h or d")),
+    };
+
+    Ok(Duration::from_secs(number * seconds))
 }
 
 // Helper function I use in the welcome message to show the user where to put the config file
@@ -88,3 +882,133 @@ pub fn get_default_config_dir() -> String {
         "your system config directory".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(name: &str, url: &str) -> Endpoint {
+        Endpoint {
+            name: name.to_string(),
+            url: url.to_string(),
+            ..Endpoint::default()
+        }
+    }
+
+    fn config(endpoints: Vec<Endpoint>) -> StatuiConfig {
+        StatuiConfig {
+            endpoints,
+            ..StatuiConfig::default()
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(
+            parse_duration(" 2 h "),
+            Ok(Duration::from_secs(2 * 60 * 60))
+        );
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(24 * 60 * 60)));
+
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("3w").is_err());
+    }
+
+    #[test]
+    fn interpolates_environment_variables() {
+        // Set in any environment the tests run in, and never changed by them
+        let path = std::env::var("PATH").unwrap();
+
+        assert_eq!(interpolate_env("plain"), Ok("plain".to_string()));
+        assert_eq!(
+            interpolate_env("a ${PATH} b ${PATH}"),
+            Ok(format!("a {path} b {path}"))
+        );
+        assert_eq!(
+            interpolate_env("left ${open"),
+            Ok("left ${open".to_string())
+        );
+        assert!(interpolate_env("${STATUI_SURELY_NOT_SET}").is_err());
+    }
+
+    #[test]
+    fn diffs_endpoints_by_name() {
+        let old = config(vec![
+            endpoint("kept", "http://kept"),
+            endpoint("moved", "http://old"),
+            endpoint("gone", "http://gone"),
+        ]);
+        let new = config(vec![
+            endpoint("kept", "http://kept"),
+            endpoint("moved", "http://new"),
+            endpoint("added", "http://added"),
+        ]);
+
+        let diff = ConfigDiff::between(&old, &new);
+        let names = |endpoints: &[Endpoint]| -> Vec<String> {
+            endpoints.iter().map(|e| e.name.clone()).collect()
+        };
+        assert_eq!(names(&diff.added), ["added"]);
+        assert_eq!(names(&diff.changed), ["moved"]);
+        assert_eq!(diff.removed, ["gone"]);
+        assert!(ConfigDiff::between(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn inherited_defaults_count_as_changes() {
+        let old = config(vec![
+            endpoint("inherits", "http://a"),
+            Endpoint {
+                interval: Some(10),
+                ..endpoint("own", "http://b")
+            },
+        ]);
+        let new = StatuiConfig {
+            default_interval: old.default_interval + 1,
+            ..old.clone()
+        };
+
+        let diff = ConfigDiff::between(&old, &new);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "inherits");
+    }
+
+    #[test]
+    fn settings_changes_keep_the_worker() {
+        let old = config(vec![endpoint("api", "http://api")]);
+        let new = config(vec![Endpoint {
+            group: Some("core".to_string()),
+            ..endpoint("api", "http://api")
+        }]);
+
+        assert!(ConfigDiff::between(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn rejects_inverted_latency_thresholds() {
+        assert!(StatuiConfig::default().validate().is_ok());
+
+        let inverted = StatuiConfig {
+            latency_warn_ms: 600,
+            ..StatuiConfig::default()
+        };
+        assert!(inverted.validate().is_err());
+
+        // Only the warn threshold is overridden, the global critical one is lower
+        let endpoint = Endpoint {
+            latency_warn_ms: Some(800),
+            ..endpoint("api", "http://api")
+        };
+        assert!(config(vec![endpoint.clone()]).validate().is_err());
+
+        let endpoint = Endpoint {
+            latency_critical_ms: Some(1000),
+            ..endpoint
+        };
+        assert!(config(vec![endpoint]).validate().is_ok());
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{Result, eyre::eyre};
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::config::{ConfigPaths, Endpoint};
+
+// Writing endpoints back into the config files.
+//
+// Edits go through toml_edit so comments, ordering and formatting in the
+// user's files survive, only the values that actually changed are touched.
+
+/// Adds `endpoint` to the config, or updates the endpoint currently named
+/// `original_name` if one is given. Returns the path of the file that was written.
+pub fn upsert_endpoint(
+    paths: &ConfigPaths,
+    original_name: Option<&str>,
+    endpoint: &Endpoint,
+) -> Result<PathBuf> {
+    let path = match original_name {
+        Some(name) => find_endpoint_source(paths, name)?,
+        None => endpoints_source(paths),
+    };
+
+    let mut doc = read_document(&path)?;
+    let endpoints = endpoints_array(&mut doc)?;
+
+    let existing = original_name.and_then(|name| find_index(endpoints, name));
+    match existing {
+        Some(i) => {
+            let table = endpoints.get_mut(i).expect("index comes from find_index");
+            write_endpoint(table, endpoint);
+        }
+        None => {
+            let mut table = Table::new();
+            table.decor_mut().set_prefix("\n");
+            write_endpoint(&mut table, endpoint);
+            endpoints.push(table);
+        }
+    }
+
+    fs::write(&path, doc.to_string())?;
+    Ok(path)
+}
+
+/// Removes the endpoint named `name` from the file it was defined in.
+/// Returns the path of the file that was written.
+pub fn delete_endpoint(paths: &ConfigPaths, name: &str) -> Result<PathBuf> {
+    let path = find_endpoint_source(paths, name)?;
+
+    let mut doc = read_document(&path)?;
+    let endpoints = endpoints_array(&mut doc)?;
+
+    let Some(i) = find_index(endpoints, name) else {
+        return Err(eyre!("Endpoint '{name}' not found in {}", path.display()));
+    };
+    endpoints.remove(i);
+
+    fs::write(&path, doc.to_string())?;
+    Ok(path)
+}
+
+/// The file an existing endpoint was defined in.
+///
+/// The local config wins over the global one, same as when loading.
+fn find_endpoint_source(paths: &ConfigPaths, name: &str) -> Result<PathBuf> {
+    for path in paths.all().into_iter().rev() {
+        let Ok(mut doc) = read_document(path) else {
+            continue;
+        };
+        if let Ok(endpoints) = endpoints_array(&mut doc)
+            && find_index(endpoints, name).is_some()
+        {
+            return Ok(path.clone());
+        }
+    }
+
+    Err(eyre!("Couldn't find the config file defining '{name}'"))
+}
+
+/// The file new endpoints should go to.
+///
+/// Arrays aren't merged between config files (the local `endpoints` replaces
+/// the global one), so new endpoints go to whichever file currently provides
+/// them to avoid hiding the others. Defaults to the local config.
+fn endpoints_source(paths: &ConfigPaths) -> PathBuf {
+    for path in paths.all().into_iter().rev() {
+        let has_endpoints = read_document(path)
+            .map(|doc| doc.get("endpoints").is_some())
+            .unwrap_or(false);
+
+        if has_endpoints {
+            return path.clone();
+        }
+    }
+
+    paths.local.clone()
+}
+
+/// Reads a config file, a missing file is treated as an empty one.
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(contents.parse::<DocumentMut>()?)
+}
+
+fn endpoints_array(doc: &mut DocumentMut) -> Result<&mut ArrayOfTables> {
+    doc.entry("endpoints")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| eyre!("'endpoints' must be written as [[endpoints]] blocks to be edited"))
+}
+
+fn find_index(endpoints: &ArrayOfTables, name: &str) -> Option<usize> {
+    endpoints
+        .iter()
+        .position(|table| table.get("name").and_then(|n| n.as_str()) == Some(name))
+}
+
+/// Writes the fields of `endpoint` into `table` leaving untouched values
+/// (and their comments) as they are.
+fn write_endpoint(table: &mut Table, endpoint: &Endpoint) {
+    set_str(table, "name", Some(&endpoint.name));
+    set_str(table, "url", Some(&endpoint.url));
+    set_int(table, "interval", endpoint.interval);
+    set_int(table, "timeout", endpoint.timeout);
+    set_str(table, "method", endpoint.method.as_deref());
+    set_headers(table, &endpoint.headers);
+}
+
+fn set_str(table: &mut Table, key: &str, new: Option<&str>) {
+    let Some(new) = new else {
+        table.remove(key);
+        return;
+    };
+
+    if table.get(key).and_then(|item| item.as_str()) != Some(new) {
+        set_value(table, key, Value::from(new));
+    }
+}
+
+fn set_int(table: &mut Table, key: &str, new: Option<u64>) {
+    let Some(new) = new else {
+        table.remove(key);
+        return;
+    };
+
+    if table.get(key).and_then(|item| item.as_integer()) != Some(new as i64) {
+        set_value(table, key, Value::from(new as i64));
+    }
+}
+
+fn set_headers(table: &mut Table, headers: &HashMap<String, String>) {
+    if headers.is_empty() {
+        table.remove("headers");
+        return;
+    }
+
+    let existing: Option<HashMap<String, String>> = table
+        .get("headers")
+        .and_then(|item| item.as_table_like())
+        .map(|t| {
+            t.iter()
+                .filter_map(|(k, v)| Some((k.to_string(), v.as_str()?.to_string())))
+                .collect()
+        });
+
+    if existing.as_ref() == Some(headers) {
+        return;
+    }
+
+    // Sorted so the written file doesn't depend on HashMap ordering
+    let mut sorted: Vec<_> = headers.iter().collect();
+    sorted.sort();
+
+    let mut inline = InlineTable::new();
+    for (key, value) in sorted {
+        inline.insert(key, Value::from(value.as_str()));
+    }
+    set_value(table, "headers", Value::InlineTable(inline));
+}
+
+/// Replaces a value while keeping the comments and spacing around the old one.
+fn set_value(table: &mut Table, key: &str, mut value: Value) {
+    if let Some(old) = table.get(key).and_then(|item| item.as_value()) {
+        *value.decor_mut() = old.decor().clone();
+    }
+    table[key] = Item::Value(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# Checked every minute
+default_interval = 60
+
+[[endpoints]]
+name = "api"   # The public API
+url = "https://api.example.com"
+interval = 30  # Busy, check it more often
+
+# Internal only
+[[endpoints]]
+name = "admin"
+url = "https://admin.example.com"
+"#;
+
+    /// A local config holding `contents` in its own temp file.
+    fn paths(test: &str, contents: &str) -> ConfigPaths {
+        let local = std::env::temp_dir().join(format!(
+            "statui-config-edit-{test}-{}.toml",
+            std::process::id()
+        ));
+        fs::write(&local, contents).unwrap();
+        ConfigPaths {
+            global: None,
+            local,
+        }
+    }
+
+    fn read(paths: &ConfigPaths) -> String {
+        let contents = fs::read_to_string(&paths.local).unwrap();
+        let _ = fs::remove_file(&paths.local);
+        contents
+    }
+
+    #[test]
+    fn updates_only_the_changed_values() {
+        let paths = paths("update", CONFIG);
+        let endpoint = Endpoint {
+            name: "api".to_string(),
+            url: "https://api.example.com/v2".to_string(),
+            interval: Some(30),
+            ..Endpoint::default()
+        };
+
+        upsert_endpoint(&paths, Some("api"), &endpoint).unwrap();
+
+        let expected = CONFIG.replace("https://api.example.com\"", "https://api.example.com/v2\"");
+        assert_eq!(read(&paths), expected);
+    }
+
+    #[test]
+    fn adds_new_endpoints_at_the_end() {
+        let paths = paths("add", CONFIG);
+        let endpoint = Endpoint {
+            name: "web".to_string(),
+            url: "https://example.com".to_string(),
+            ..Endpoint::default()
+        };
+
+        upsert_endpoint(&paths, None, &endpoint).unwrap();
+
+        let expected =
+            format!("{CONFIG}\n[[endpoints]]\nname = \"web\"\nurl = \"https://example.com\"\n");
+        assert_eq!(read(&paths), expected);
+    }
+
+    #[test]
+    fn deletes_only_the_target() {
+        let paths = paths("delete", CONFIG);
+
+        delete_endpoint(&paths, "admin").unwrap();
+
+        let contents = read(&paths);
+        assert!(contents.starts_with("# Checked every minute\ndefault_interval = 60\n"));
+        assert!(contents.contains("name = \"api\"   # The public API\n"));
+        assert!(contents.contains("interval = 30  # Busy, check it more often\n"));
+        assert!(!contents.contains("admin"));
+    }
+
+    #[test]
+    fn deleting_a_missing_endpoint_fails() {
+        let paths = paths("missing", CONFIG);
+
+        assert!(delete_endpoint(&paths, "nope").is_err());
+        assert_eq!(read(&paths), CONFIG);
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use clap::ValueEnum;
+use color_eyre::{Result, eyre::bail};
+use serde::Serialize;
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    backend::CheckStatus,
+    cli::ExportArgs,
+    config::{ConfigPaths, HistoryConfig, StatuiConfig},
+    history::{self, CheckRecord},
+    state::EndpointState,
+    stats::incidents::{self, Health},
+};
+
+// Exports the check history as CSV, JSON or NDJSON, either from the
+// `statui export` command or for a single endpoint from the TUI.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// A check result as it's exported.
+#[derive(Debug, Serialize)]
+struct ExportRow {
+    timestamp: String,
+    endpoint: String,
+    status: Health,
+    code: Option<u16>,
+    error_class: Option<&'static str>,
+    // The status as shown in the TUI (e.g. "200 OK", "Timeout")
+    message: String,
+    latency_ms: u64,
+}
+
+impl ExportRow {
+    fn new(record: &CheckRecord) -> Self {
+        let timestamp: DateTime<Utc> = record.time().into();
+        let code = match record.status {
+            CheckStatus::Success { code, .. } => Some(code),
+            CheckStatus::Error { .. } => None,
+        };
+
+        ExportRow {
+            timestamp: timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            endpoint: record.name.clone(),
+            status: Health::of(&record.to_result()),
+            code,
+            error_class: record.status.error_class(),
+            message: incidents::describe(&record.status),
+            latency_ms: record.latency_ms,
+        }
+    }
+}
+
+const CSV_HEADER: &str = "timestamp,endpoint,status,code,error_class,message,latency_ms";
+
+/// Writes the records to `out` in the given format.
+pub fn write(
+    records: &[CheckRecord],
+    format: ExportFormat,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let rows = records.iter().map(ExportRow::new);
+
+    match format {
+        ExportFormat::Csv => {
+            writeln!(out, "{CSV_HEADER}")?;
+            for row in rows {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    row.timestamp,
+                    csv_field(&row.endpoint),
+                    row.status.label().to_lowercase(),
+                    row.code.map(|c| c.to_string()).unwrap_or_default(),
+                    row.error_class.unwrap_or_default(),
+                    csv_field(&row.message),
+                    row.latency_ms
+                )?;
+            }
+        }
+        ExportFormat::Json => {
+            let rows: Vec<ExportRow> = rows.collect();
+            serde_json::to_writer_pretty(&mut *out, &rows)?;
+            writeln!(out)?;
+        }
+        ExportFormat::Ndjson => {
+            for row in rows {
+                serde_json::to_writer(&mut *out, &row)?;
+                writeln!(out)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs `statui export`, writing the history to stdout.
+pub fn run(args: &ExportArgs) -> Result<()> {
+    let config = StatuiConfig::load(&ConfigPaths::new(args.config.clone()))?;
+    if !config.history.enabled {
+        bail!("History is disabled in the config, there's nothing to export");
+    }
+
+    let since = SystemTime::now()
+        .checked_sub(args.since)
+        .unwrap_or(UNIX_EPOCH);
+
+    let records: Vec<CheckRecord> = history::load_since(&config.history, since)
+        .into_iter()
+        .filter(|record| {
+            args.endpoint
+                .as_ref()
+                .is_none_or(|name| record.name == *name)
+        })
+        .collect();
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    write(&records, args.format, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// How an export started from the TUI went.
+pub struct ExportOutcome {
+    pub endpoint: String,
+    // The file written and the number of checks in it
+    pub result: io::Result<(PathBuf, usize)>,
+}
+
+/// Exports everything statui knows about the endpoint to a CSV file in the
+/// working directory. Reading the history can take a while so it runs on a
+/// blocking thread, the outcome is sent to `tx` once it's done.
+///
+/// Without a history on disk only the recent checks kept in memory are exported.
+pub fn spawn_export(config: &StatuiConfig, state: &EndpointState, tx: Sender<ExportOutcome>) {
+    let endpoint = state.name.clone();
+    let history = config.history.enabled.then(|| config.history.clone());
+    let recent: Vec<CheckRecord> = state
+        .recent_checks
+        .iter()
+        .rev()
+        .map(|(at, result, _)| CheckRecord::new(*at, result))
+        .collect();
+
+    tokio::task::spawn_blocking(move || {
+        let result = export_endpoint(&endpoint, history.as_ref(), recent);
+        let _ = tx.blocking_send(ExportOutcome { endpoint, result });
+    });
+}
+
+fn export_endpoint(
+    endpoint: &str,
+    history: Option<&HistoryConfig>,
+    recent: Vec<CheckRecord>,
+) -> io::Result<(PathBuf, usize)> {
+    let records: Vec<CheckRecord> = match history {
+        Some(history) => history::load_since(history, UNIX_EPOCH)
+            .into_iter()
+            .filter(|record| record.name == endpoint)
+            .collect(),
+        None => recent,
+    };
+
+    let format = ExportFormat::Csv;
+    let now: DateTime<Local> = SystemTime::now().into();
+    let file_name = format!(
+        "statui-{}-{}.{}",
+        file_safe(endpoint),
+        now.format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    let path = env::current_dir()?.join(file_name);
+
+    let mut out = BufWriter::new(File::create(&path)?);
+    write(&records, format, &mut out)?;
+    out.flush()?;
+
+    Ok((path, records.len()))
+}
+
+/// Quotes a CSV field if it needs to be.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<CheckRecord> {
+        vec![
+            CheckRecord {
+                timestamp_ms: 1_700_000_000_000,
+                name: "api".to_string(),
+                status: CheckStatus::Success {
+                    code: 200,
+                    text: "OK".to_string(),
+                },
+                latency_ms: 42,
+            },
+            CheckRecord {
+                timestamp_ms: 1_700_000_030_500,
+                name: "api, \"eu\"".to_string(),
+                status: CheckStatus::Error {
+                    message: "Timeout".to_string(),
+                },
+                latency_ms: 5000,
+            },
+        ]
+    }
+
+    fn export(format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        write(&records(), format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn writes_csv() {
+        assert_eq!(
+            export(ExportFormat::Csv),
+            "timestamp,endpoint,status,code,error_class,message,latency_ms\n\
+             2023-11-14T22:13:20.000Z,api,up,200,,200 OK,42\n\
+             2023-11-14T22:13:50.500Z,\"api, \"\"eu\"\"\",down,,timeout,Timeout,5000\n"
+        );
+    }
+
+    #[test]
+    fn writes_json_and_ndjson() {
+        let json: serde_json::Value = serde_json::from_str(&export(ExportFormat::Json)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[0]["timestamp"], "2023-11-14T22:13:20.000Z");
+        assert_eq!(json[0]["code"], 200);
+        assert_eq!(json[0]["error_class"], serde_json::Value::Null);
+        assert_eq!(json[1]["endpoint"], "api, \"eu\"");
+        assert_eq!(json[1]["error_class"], "timeout");
+        assert_eq!(json[1]["latency_ms"], 5000);
+
+        let ndjson = export(ExportFormat::Ndjson);
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, json.as_array().unwrap().clone());
+    }
+
+    #[test]
+    fn file_names_are_safe() {
+        assert_eq!(file_safe("api/v1 (eu)"), "api-v1--eu-");
+    }
+}
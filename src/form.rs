@@ -0,0 +1,281 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    config::{self, Endpoint},
+    silence::{self, SilenceKind},
+    state::App,
+};
+
+/// The fields of the endpoint form in the order they're displayed.
+pub const FORM_FIELDS: [&str; 6] = ["Name", "URL", "Method", "Interval", "Timeout", "Headers"];
+
+const NAME: usize = 0;
+const URL: usize = 1;
+const METHOD: usize = 2;
+const INTERVAL: usize = 3;
+const TIMEOUT: usize = 4;
+const HEADERS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormKind {
+    Add,
+    Edit { original_name: String },
+}
+
+/// What the app should do after a key was handled by the form.
+pub enum FormOutcome {
+    Continue,
+    Cancel,
+    Submit(Box<Endpoint>),
+}
+
+/// State of the add/edit endpoint overlay.
+///
+/// Every field is edited as plain text and only validated on submit.
+pub struct EndpointForm {
+    pub kind: FormKind,
+    pub values: [String; FORM_FIELDS.len()],
+    pub focused: usize,
+    pub error: Option<String>,
+
+    // Settings the form doesn't expose are carried over when editing
+    base: Endpoint,
+}
+
+impl EndpointForm {
+    pub fn new_add() -> Self {
+        EndpointForm {
+            kind: FormKind::Add,
+            values: Default::default(),
+            focused: NAME,
+            error: None,
+            base: Endpoint::default(),
+        }
+    }
+
+    pub fn new_edit(endpoint: &Endpoint) -> Self {
+        let mut headers: Vec<String> = endpoint
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{k}: {v}"))
+            .collect();
+        headers.sort();
+
+        EndpointForm {
+            kind: FormKind::Edit {
+                original_name: endpoint.name.clone(),
+            },
+            values: [
+                endpoint.name.clone(),
+                endpoint.url.clone(),
+                endpoint.method.clone().unwrap_or_default(),
+                endpoint.interval.map(|i| i.to_string()).unwrap_or_default(),
+                endpoint.timeout.map(|t| t.to_string()).unwrap_or_default(),
+                headers.join("; "),
+            ],
+            focused: NAME,
+            error: None,
+            base: endpoint.clone(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match &self.kind {
+            FormKind::Add => "Add Endpoint".to_string(),
+            FormKind::Edit { original_name } => format!("Edit Endpoint: {original_name}"),
+        }
+    }
+
+    /// Handles a key press while the form is open.
+    pub fn handle_key(&mut self, key: KeyEvent, app: &App) -> FormOutcome {
+        match key.code {
+            KeyCode::Esc => return FormOutcome::Cancel,
+            KeyCode::Enter => match self.validate(app) {
+                Ok(endpoint) => return FormOutcome::Submit(Box::new(endpoint)),
+                Err(err) => self.error = Some(err),
+            },
+            KeyCode::Tab | KeyCode::Down => {
+                self.focused = (self.focused + 1) % FORM_FIELDS.len();
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focused = (self.focused + FORM_FIELDS.len() - 1) % FORM_FIELDS.len();
+            }
+            KeyCode::Backspace => {
+                self.values[self.focused].pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.values[self.focused].push(c);
+            }
+            _ => {}
+        }
+
+        FormOutcome::Continue
+    }
+
+    /// Turns the form values into an Endpoint or returns a message
+    /// describing the first invalid field.
+    fn validate(&self, app: &App) -> Result<Endpoint, String> {
+        let name = self.values[NAME].trim().to_string();
+        if name.is_empty() {
+            return Err("Name can't be empty".to_string());
+        }
+
+        let renamed = match &self.kind {
+            FormKind::Add => true,
+            FormKind::Edit { original_name } => *original_name != name,
+        };
+        if renamed && app.endpoint_states.contains_key(&name) {
+            return Err(format!("An endpoint named '{name}' already exists"));
+        }
+
+        let url = self.values[URL].trim().to_string();
+        match reqwest::Url::parse(&url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            Ok(_) => return Err("URL must start with http:// or https://".to_string()),
+            Err(err) => return Err(format!("Invalid URL: {err}")),
+        }
+
+        let method = optional(&self.values[METHOD]).map(|m| m.to_uppercase());
+        if let Some(method) = &method
+            && reqwest::Method::from_bytes(method.as_bytes()).is_err()
+        {
+            return Err(format!("Invalid method '{method}'"));
+        }
+
+        let interval = parse_seconds("Interval", &self.values[INTERVAL])?;
+        let timeout = parse_seconds("Timeout", &self.values[TIMEOUT])?;
+        let headers = parse_headers(&self.values[HEADERS])?;
+
+        Ok(Endpoint {
+            name,
+            url,
+            interval,
+            timeout,
+            method,
+            headers,
+            ..self.base.clone()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    Ack,
+    Snooze,
+}
+
+/// What the app should do after a key was handled by the prompt.
+pub enum PromptOutcome {
+    Continue,
+    Cancel,
+    Submit(SilenceKind),
+}
+
+/// State of the single line overlay asking for an ack note or how long
+/// to snooze an endpoint for.
+pub struct SilencePrompt {
+    pub kind: PromptKind,
+    pub endpoint: String,
+    pub value: String,
+    pub error: Option<String>,
+}
+
+impl SilencePrompt {
+    pub fn new(kind: PromptKind, endpoint: String) -> Self {
+        let value = match kind {
+            PromptKind::Ack => String::new(),
+            PromptKind::Snooze => "1h".to_string(),
+        };
+
+        SilencePrompt {
+            kind,
+            endpoint,
+            value,
+            error: None,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self.kind {
+            PromptKind::Ack => format!("Acknowledge: {}", self.endpoint),
+            PromptKind::Snooze => format!("Snooze: {}", self.endpoint),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self.kind {
+            PromptKind::Ack => "Note",
+            PromptKind::Snooze => "For",
+        }
+    }
+
+    /// Handles a key press while the prompt is open.
+    pub fn handle_key(&mut self, key: KeyEvent) -> PromptOutcome {
+        match key.code {
+            KeyCode::Esc => return PromptOutcome::Cancel,
+            KeyCode::Enter => match self.kind {
+                PromptKind::Ack => {
+                    let note = optional(&self.value);
+                    return PromptOutcome::Submit(SilenceKind::Ack { note });
+                }
+                PromptKind::Snooze => match config::parse_duration(&self.value) {
+                    Ok(duration) => {
+                        let until = SystemTime::now() + duration;
+                        let until_ms = silence::to_millis(until);
+                        return PromptOutcome::Submit(SilenceKind::Snooze { until_ms });
+                    }
+                    Err(err) => self.error = Some(err),
+                },
+            },
+            KeyCode::Backspace => {
+                self.value.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.value.push(c);
+            }
+            _ => {}
+        }
+
+        PromptOutcome::Continue
+    }
+}
+
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn parse_seconds(field: &str, value: &str) -> Result<Option<u64>, String> {
+    let Some(value) = optional(value) else {
+        return Ok(None);
+    };
+
+    match value.parse::<u64>() {
+        Ok(0) | Err(_) => Err(format!(
+            "{field} must be a positive number of seconds (or empty for the default)"
+        )),
+        Ok(seconds) => Ok(Some(seconds)),
+    }
+}
+
+/// Parses headers written as `Key: Value; Other-Key: Value`.
+fn parse_headers(value: &str) -> Result<HashMap<String, String>, String> {
+    let mut headers = HashMap::new();
+
+    for pair in value.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((key, value)) = pair.split_once(':') else {
+            return Err(format!("Header '{pair}' must look like 'Key: Value'"));
+        };
+
+        let key = key.trim();
+        if reqwest::header::HeaderName::from_bytes(key.as_bytes()).is_err() {
+            return Err(format!("Invalid header name '{key}'"));
+        }
+
+        headers.insert(key.to_string(), value.trim().to_string());
+    }
+
+    Ok(headers)
+}
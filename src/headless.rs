@@ -0,0 +1,302 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use color_eyre::Result;
+use serde_json::{Value, json};
+use tokio::{
+    sync::mpsc::{Receiver, Sender},
+    task::JoinHandle,
+    time::{interval, timeout},
+};
+
+use crate::{
+    app,
+    backend::{BackendCommand, CheckResult, CheckStatus},
+    config::StatuiConfig,
+    notify::{NotifierEvent, hook::HookResult},
+    state::{App, StatusLevel},
+    stats::incidents::{self, Health},
+    status_page,
+    watcher::ConfigEvent,
+};
+
+// Headless mode runs the checks, notifications, hooks, history and
+// exporters without the TUI. Everything that would show up on screen is
+// logged to stdout as JSON lines instead.
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+// How long notifications, the history and the sinks get to finish on shutdown
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs until SIGINT or SIGTERM is received.
+pub async fn run(
+    app: &mut App,
+    mut rx: Receiver<CheckResult>,
+    backend_tx: Sender<BackendCommand>,
+    mut config_rx: Receiver<ConfigEvent>,
+    mut notifier_rx: Receiver<NotifierEvent>,
+    notifier: JoinHandle<()>,
+) -> Result<()> {
+    log(
+        "info",
+        "started",
+        json!({
+            "config": app.config_paths.local.display().to_string(),
+            "endpoints": app.endpoint_order.len(),
+        }),
+    );
+
+    let mut ticker = interval(TICK_INTERVAL);
+    let mut last_status: Option<Instant> = None;
+    let mut status_page = StatusPageWriter::default();
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            signal = &mut shutdown => {
+                log("info", "shutdown", json!({ "signal": signal }));
+                finish(app, notifier_rx, notifier).await;
+                return Ok(());
+            }
+            Some(result) = rx.recv() => on_result(app, result),
+            Some(event) = config_rx.recv() => {
+                app::on_config_event(app, event, &backend_tx);
+            }
+            Some(event) = notifier_rx.recv() => {
+                log_notifier_event(&event);
+                app::on_notifier_event(app, event);
+
+                // Already logged above
+                last_status = app.status_message.as_ref().map(|message| message.created_at);
+            }
+            _ = ticker.tick() => {
+                app::retry_config_reload(app, &backend_tx);
+                app.expire_silences();
+                app.report_sink_failures();
+                app.report_history_drops();
+                status_page.tick(&app.config);
+            }
+        }
+
+        // Anything else the TUI would have shown in its status line
+        if let Some(message) = &app.status_message
+            && last_status != Some(message.created_at)
+        {
+            last_status = Some(message.created_at);
+            let level = match message.level {
+                StatusLevel::Info => "info",
+                StatusLevel::Error => "error",
+            };
+            log(level, "status", json!({ "message": message.text }));
+        }
+    }
+}
+
+/// Lets the notifier deliver what it still has, the history writer flush
+/// and the sinks push their last points, for at most `SHUTDOWN_TIMEOUT`.
+async fn finish(app: &mut App, mut notifier_rx: Receiver<NotifierEvent>, notifier: JoinHandle<()>) {
+    let (history, sinks) = app.shutdown();
+
+    let notified = async {
+        // Closes once the notifier and everything it started are done
+        while let Some(event) = notifier_rx.recv().await {
+            log_notifier_event(&event);
+        }
+        let _ = notifier.await;
+
+        if let Some(history) = history {
+            let _ = tokio::task::spawn_blocking(move || history.close()).await;
+        }
+    };
+    // A slow notification shouldn't hold back the last points
+    let pushed = async {
+        if let Some(sinks) = sinks {
+            sinks.close().await;
+        }
+    };
+    let finished = async { tokio::join!(notified, pushed) };
+
+    if timeout(SHUTDOWN_TIMEOUT, finished).await.is_err() {
+        log(
+            "error",
+            "shutdown",
+            json!({ "message": "Gave up waiting for notifications, the history and the sinks to finish" }),
+        );
+    }
+}
+
+/// Records the result and logs it along with the state change it caused.
+fn on_result(app: &mut App, result: CheckResult) {
+    let name = result.name.clone();
+    let previous = app
+        .endpoint_states
+        .get(&name)
+        .and_then(|state| state.health);
+
+    if !app.on_result(result) {
+        return;
+    }
+
+    let Some(state) = app.endpoint_states.get(&name) else {
+        return;
+    };
+    let Some((_, result, _)) = state.recent_checks.front() else {
+        return;
+    };
+
+    let code = match result.status {
+        CheckStatus::Success { code, .. } => Some(code),
+        CheckStatus::Error { .. } => None,
+    };
+    let level = match state.health {
+        Some(Health::Down) => "error",
+        Some(Health::Degraded) => "warn",
+        _ => "info",
+    };
+    log(
+        level,
+        "check",
+        json!({
+            "endpoint": state.name,
+            "url": state.url,
+            "status": state.health,
+            "code": code,
+            "error_class": result.status.error_class(),
+            "message": incidents::describe(&result.status),
+            "latency_ms": result.latency.as_millis() as u64,
+        }),
+    );
+
+    if let (Some(from), Some(to)) = (previous, state.health)
+        && from != to
+    {
+        let level = if to == Health::Up { "info" } else { "error" };
+        log(
+            level,
+            "state_change",
+            json!({
+                "endpoint": state.name,
+                "from": from,
+                "to": to,
+                "reason": state.health_reason,
+            }),
+        );
+    }
+}
+
+/// Keeps the status page up to date when `out` is set in [status_page].
+#[derive(Default)]
+struct StatusPageWriter {
+    last_run: Option<Instant>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl StatusPageWriter {
+    fn tick(&mut self, config: &StatuiConfig) {
+        let Some(out) = config.status_page.out.clone() else {
+            return;
+        };
+
+        let interval = Duration::from_secs(config.status_page.interval);
+        let due = self.last_run.is_none_or(|last| last.elapsed() >= interval);
+        let busy = self.task.as_ref().is_some_and(|task| !task.is_finished());
+        if !due || busy {
+            return;
+        }
+        self.last_run = Some(Instant::now());
+
+        // Reading the history can take a while, keep it off the runtime
+        let config = config.clone();
+        self.task = Some(tokio::task::spawn_blocking(
+            move || match status_page::write(&config, &out) {
+                Ok(path) => log(
+                    "info",
+                    "status_page",
+                    json!({ "path": path.display().to_string() }),
+                ),
+                Err(err) => log(
+                    "error",
+                    "status_page_failed",
+                    json!({ "error": err.to_string() }),
+                ),
+            },
+        ));
+    }
+}
+
+fn log_notifier_event(event: &NotifierEvent) {
+    match event {
+        NotifierEvent::DeliveryFailed(failure) => log(
+            "error",
+            "notification_failed",
+            json!({
+                "channel": failure.channel,
+                "endpoint": failure.endpoint,
+                "error": failure.error,
+            }),
+        ),
+        NotifierEvent::HookFinished(outcome) => {
+            let level = if outcome.is_success() {
+                "info"
+            } else {
+                "error"
+            };
+            let exit_code = match &outcome.result {
+                HookResult::Exited { code, .. } => *code,
+                _ => None,
+            };
+            log(
+                level,
+                "hook",
+                json!({
+                    "endpoint": outcome.endpoint,
+                    "hook": outcome.event,
+                    "exit_code": exit_code,
+                    "message": outcome.describe(),
+                    "duration_ms": outcome.duration.as_millis() as u64,
+                }),
+            );
+        }
+    }
+}
+
+/// Prints a JSON line with the time, level and event, followed by `fields`.
+fn log(level: &str, event: &str, fields: Value) {
+    let time: DateTime<Utc> = SystemTime::now().into();
+    let mut line = json!({
+        "time": time.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "level": level,
+        "event": event,
+    });
+
+    if let (Some(line), Value::Object(fields)) = (line.as_object_mut(), fields) {
+        line.extend(fields);
+    }
+    println!("{line}");
+}
+
+/// Resolves with the name of the signal once SIGINT or SIGTERM is received.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+            let _ = tokio::signal::ctrl_c().await;
+            return "SIGINT";
+        };
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = terminate.recv() => "SIGTERM",
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "SIGINT"
+    }
+}
@@ -0,0 +1,354 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+use crate::{
+    backend::{CheckResult, CheckStatus},
+    config::{APP_NAME, APP_ORGANIZATION, APP_QUALIFIER, HistoryConfig},
+};
+
+// Persistent check history.
+//
+// Every check result is appended as a JSON line to a segment file, one
+// segment per UTC day (checks-YYYY-MM-DD.ndjson). Whole segments are
+// deleted once they fall out of the retention period, which keeps
+// cleanup cheap and never rewrites existing data.
+
+const SEGMENT_PREFIX: &str = "checks-";
+const SEGMENT_EXTENSION: &str = "ndjson";
+const SEGMENT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+const WRITE_BUFFER_SIZE: usize = 1024;
+
+/// A check result as it's stored on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    pub name: String,
+    pub status: CheckStatus,
+    pub latency_ms: u64,
+}
+
+impl CheckRecord {
+    pub fn new(at: SystemTime, result: &CheckResult) -> Self {
+        CheckRecord {
+            timestamp_ms: at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            name: result.name.clone(),
+            status: result.status.clone(),
+            latency_ms: result.latency.as_millis() as u64,
+        }
+    }
+
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp_ms)
+    }
+
+    pub fn to_result(&self) -> CheckResult {
+        CheckResult {
+            name: self.name.clone(),
+            status: self.status.clone(),
+            latency: Duration::from_millis(self.latency_ms),
+            cert_expiry: None,
+            generation: 0,
+        }
+    }
+}
+
+/// Handle used to append records to the history without blocking.
+///
+/// The actual writing happens on a dedicated thread, if it falls behind
+/// records are dropped instead of stalling the caller.
+pub struct HistoryWriter {
+    tx: Sender<CheckRecord>,
+    thread: thread::JoinHandle<()>,
+    // Records dropped since the last `take_dropped`
+    dropped: usize,
+}
+
+impl HistoryWriter {
+    /// Starts the writer thread for the history directory in `config`.
+    ///
+    /// Returns None if history is disabled or there's nowhere to store it.
+    pub fn spawn(config: &HistoryConfig) -> io::Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let Some(dir) = history_dir(config) else {
+            return Ok(None);
+        };
+        fs::create_dir_all(&dir)?;
+
+        let retention = Duration::from_secs(config.retention_days * 24 * 60 * 60);
+        let (tx, rx) = mpsc::channel(WRITE_BUFFER_SIZE);
+
+        let thread = thread::spawn(move || run_writer(dir, retention, rx));
+
+        Ok(Some(HistoryWriter {
+            tx,
+            thread,
+            dropped: 0,
+        }))
+    }
+
+    pub fn append(&mut self, record: CheckRecord) {
+        if self.tx.try_send(record).is_err() {
+            self.dropped += 1;
+        }
+    }
+
+    /// The number of records dropped since the last call.
+    pub fn take_dropped(&mut self) -> usize {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// Blocks until the records appended so far are written and flushed.
+    pub fn close(self) {
+        drop(self.tx);
+        let _ = self.thread.join();
+    }
+}
+
+/// Resolves the directory the history segments are stored in.
+pub fn history_dir(config: &HistoryConfig) -> Option<PathBuf> {
+    if let Some(dir) = &config.dir {
+        return Some(dir.clone());
+    }
+
+    ProjectDirs::from(APP_QUALIFIER, APP_ORGANIZATION, APP_NAME)
+        .map(|proj_dirs| proj_dirs.data_dir().join("history"))
+}
+
+/// Loads every record newer than `since`, sorted by time.
+///
+/// Missing directories, unreadable segments and corrupt lines (like a
+/// half written line after a crash, or one that isn't even UTF-8) are skipped.
+pub fn load_since(config: &HistoryConfig, since: SystemTime) -> Vec<CheckRecord> {
+    let Some(dir) = history_dir(config) else {
+        return Vec::new();
+    };
+
+    let since_date = DateTime::<Utc>::from(since).date_naive();
+    let since_ms = since
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    let mut segments: Vec<(NaiveDate, PathBuf)> = list_segments(&dir)
+        .into_iter()
+        .filter(|(date, _)| *date >= since_date)
+        .collect();
+    segments.sort();
+
+    // Split on raw bytes so a line that isn't valid UTF-8 only fails to parse
+    // instead of ending the segment, a read error still does
+    let mut records: Vec<CheckRecord> = segments
+        .iter()
+        .filter_map(|(_, path)| File::open(path).ok())
+        .flat_map(|file| BufReader::new(file).split(b'\n').map_while(Result::ok))
+        .filter_map(|line| serde_json::from_slice::<CheckRecord>(&line).ok())
+        .filter(|record| record.timestamp_ms >= since_ms)
+        .collect();
+
+    records.sort_by_key(|record| record.timestamp_ms);
+    records
+}
+
+fn run_writer(dir: PathBuf, retention: Duration, mut rx: Receiver<CheckRecord>) {
+    let mut current: Option<(NaiveDate, BufWriter<File>)> = None;
+    remove_expired_segments(&dir, retention);
+
+    while let Some(record) = rx.blocking_recv() {
+        let mut next = Some(record);
+
+        // Write everything that's already queued up before flushing
+        while let Some(record) = next {
+            let date = DateTime::<Utc>::from(record.time()).date_naive();
+
+            if current.as_ref().is_none_or(|(d, _)| *d != date) {
+                if let Some((_, mut writer)) = current.take() {
+                    let _ = writer.flush();
+                }
+
+                // A new day is a good time to clean up
+                remove_expired_segments(&dir, retention);
+                current = open_segment(&dir, date).map(|file| (date, BufWriter::new(file)));
+            }
+
+            if let Some((_, writer)) = current.as_mut()
+                && let Ok(line) = serde_json::to_string(&record)
+            {
+                let _ = writeln!(writer, "{line}");
+            }
+
+            next = rx.try_recv().ok();
+        }
+
+        if let Some((_, writer)) = current.as_mut() {
+            let _ = writer.flush();
+        }
+    }
+}
+
+fn open_segment(dir: &Path, date: NaiveDate) -> Option<File> {
+    let path = dir.join(segment_file_name(date));
+    OpenOptions::new().create(true).append(true).open(path).ok()
+}
+
+fn segment_file_name(date: NaiveDate) -> String {
+    format!(
+        "{SEGMENT_PREFIX}{}.{SEGMENT_EXTENSION}",
+        date.format(SEGMENT_DATE_FORMAT)
+    )
+}
+
+fn list_segments(dir: &Path) -> Vec<(NaiveDate, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let stem = path
+                .file_name()?
+                .to_str()?
+                .strip_prefix(SEGMENT_PREFIX)?
+                .strip_suffix(&format!(".{SEGMENT_EXTENSION}"))?
+                .to_string();
+            let date = NaiveDate::parse_from_str(&stem, SEGMENT_DATE_FORMAT).ok()?;
+            Some((date, path))
+        })
+        .collect()
+}
+
+/// Deletes the segments that are entirely older than the retention period.
+fn remove_expired_segments(dir: &Path, retention: Duration) {
+    let Some(cutoff) = SystemTime::now().checked_sub(retention) else {
+        return;
+    };
+    let cutoff_date = DateTime::<Utc>::from(cutoff).date_naive();
+
+    for (date, path) in list_segments(dir) {
+        if date < cutoff_date {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// A history in its own temp directory.
+    fn config(test: &str) -> HistoryConfig {
+        let dir =
+            std::env::temp_dir().join(format!("statui-history-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        HistoryConfig {
+            dir: Some(dir),
+            ..HistoryConfig::default()
+        }
+    }
+
+    fn record(name: &str, at: SystemTime) -> CheckRecord {
+        CheckRecord {
+            timestamp_ms: at.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+            name: name.to_string(),
+            status: CheckStatus::Success {
+                code: 200,
+                text: "OK".to_string(),
+            },
+            latency_ms: 42,
+        }
+    }
+
+    fn names(records: &[CheckRecord]) -> Vec<&str> {
+        records.iter().map(|record| record.name.as_str()).collect()
+    }
+
+    #[test]
+    fn writes_and_loads_records_in_order() {
+        let config = config("roundtrip");
+        let now = SystemTime::now();
+
+        let mut writer = HistoryWriter::spawn(&config).unwrap().unwrap();
+        writer.append(record("today", now));
+        writer.append(record("two days ago", now - 2 * DAY));
+        writer.append(record("yesterday", now - DAY));
+        writer.append(record("too old", now - 5 * DAY));
+        assert_eq!(writer.take_dropped(), 0);
+        writer.close();
+
+        let dir = config.dir.clone().unwrap();
+        assert_eq!(list_segments(&dir).len(), 4);
+
+        let records = load_since(&config, now - 3 * DAY);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(names(&records), ["two days ago", "yesterday", "today"]);
+        assert_eq!(records[2].latency_ms, 42);
+    }
+
+    #[test]
+    fn skips_corrupt_lines() {
+        let config = config("corrupt");
+        let dir = config.dir.clone().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+
+        let now = SystemTime::now();
+        let line = |name| serde_json::to_string(&record(name, now)).unwrap();
+        let mut contents = Vec::new();
+        writeln!(contents, "{}", line("before")).unwrap();
+        contents.extend_from_slice(b"\xff\xfe not utf-8\n");
+        writeln!(contents, "{{\"timestamp_ms\": 1, \"na").unwrap();
+        writeln!(contents, "{}", line("after")).unwrap();
+
+        let date = DateTime::<Utc>::from(now).date_naive();
+        fs::write(dir.join(segment_file_name(date)), contents).unwrap();
+
+        let records = load_since(&config, now - DAY);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(names(&records), ["before", "after"]);
+    }
+
+    #[test]
+    fn removes_segments_past_the_retention() {
+        let mut config = config("retention");
+        config.retention_days = 7;
+        let dir = config.dir.clone().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+
+        let today = Utc::now().date_naive();
+        let expired = dir.join(segment_file_name(today - chrono::Days::new(8)));
+        let kept = dir.join(segment_file_name(today - chrono::Days::new(6)));
+        let unrelated = dir.join("notes.txt");
+        for path in [&expired, &kept, &unrelated] {
+            fs::write(path, "").unwrap();
+        }
+
+        // The writer cleans up when it starts
+        HistoryWriter::spawn(&config).unwrap().unwrap().close();
+
+        let (expired, kept, unrelated) = (expired.exists(), kept.exists(), unrelated.exists());
+        let _ = fs::remove_dir_all(&dir);
+        assert!(!expired);
+        assert!(kept);
+        assert!(unrelated);
+    }
+}
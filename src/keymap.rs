@@ -21,6 +21,24 @@ pub fn default_keymap() -> KeyMap {
     // Toggle Inspector Pane
     m.insert((KeyCode::Char('i'), KeyModifiers::NONE), ToggleInspect);
 
+    // Backend controls
+    m.insert((KeyCode::Char('r'), KeyModifiers::NONE), CheckNow);
+    m.insert((KeyCode::Char('p'), KeyModifiers::NONE), TogglePause);
+    m.insert((KeyCode::Char('P'), KeyModifiers::NONE), TogglePauseAll);
+    m.insert((KeyCode::Char('P'), KeyModifiers::SHIFT), TogglePauseAll);
+
+    // Editing endpoints
+    m.insert((KeyCode::Char('a'), KeyModifiers::NONE), AddEndpoint);
+    m.insert((KeyCode::Char('e'), KeyModifiers::NONE), EditEndpoint);
+    m.insert((KeyCode::Char('d'), KeyModifiers::NONE), DeleteEndpoint);
+    m.insert((KeyCode::Char('x'), KeyModifiers::NONE), ExportHistory);
+
+    // Silencing notifications
+    m.insert((KeyCode::Char('A'), KeyModifiers::NONE), Acknowledge);
+    m.insert((KeyCode::Char('A'), KeyModifiers::SHIFT), Acknowledge);
+    m.insert((KeyCode::Char('z'), KeyModifiers::NONE), Snooze);
+    m.insert((KeyCode::Char('m'), KeyModifiers::NONE), ToggleMute);
+
     // Sorting
     m.insert((KeyCode::Char('s'), KeyModifiers::NONE), CycleSort);
     m.insert(
@@ -29,6 +47,10 @@ pub fn default_keymap() -> KeyMap {
     );
 
     // Panels and Views
+    m.insert((KeyCode::Char('1'), KeyModifiers::NONE), ShowEndpoints);
+    m.insert((KeyCode::Char('2'), KeyModifiers::NONE), ShowIncidents);
+    m.insert((KeyCode::Char('3'), KeyModifiers::NONE), ShowAlerts);
+    m.insert((KeyCode::Enter, KeyModifiers::NONE), Select);
     m.insert((KeyCode::Char('?'), KeyModifiers::NONE), Help);
     m.insert((KeyCode::Char('/'), KeyModifiers::NONE), Search);
 
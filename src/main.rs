@@ -1,41 +1,139 @@
 mod actions;
+mod alerts;
+mod api;
 mod app;
 mod backend;
+mod check;
+mod cli;
 mod config;
+mod config_edit;
+mod export;
+mod form;
+mod headless;
+mod history;
 mod keymap;
+mod metrics;
+mod notify;
+mod otel;
+mod silence;
+mod sinks;
 mod state;
+mod stats;
+mod status_page;
 mod ui;
+mod watcher;
 
-use std::{env, process};
+use std::process;
 
+use clap::Parser;
 use color_eyre::Result;
+use tokio::net::TcpListener;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 
-use crate::backend::CheckResult;
-use crate::config::StatuiConfig;
+use crate::backend::{BackendCommand, CheckResult};
+use crate::cli::{Cli, Command};
+use crate::config::{ConfigPaths, StatuiConfig, interpolate_env};
+use crate::notify::{Notification, NotifierEvent};
 use crate::state::App;
+use crate::watcher::ConfigEvent;
 
 const RESULT_BUFFER_SIZE: usize = 100;
+const COMMAND_BUFFER_SIZE: usize = 32;
+const CONFIG_EVENT_BUFFER_SIZE: usize = 4;
+const NOTIFICATION_BUFFER_SIZE: usize = 32;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let conf = StatuiConfig::build(env::args()).unwrap_or_else(|err| {
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Export(args)) => return export::run(&args),
+        Some(Command::StatusPage(args)) => return status_page::run(&args),
+        Some(Command::Check(args)) => {
+            let code = check::run(&args).await.unwrap_or_else(|err| {
+                eprintln!("{err}");
+                check::EXIT_UNKNOWN
+            });
+            process::exit(code);
+        }
+        None => {}
+    }
+
+    let config_paths = ConfigPaths::new(cli.config);
+    let conf = StatuiConfig::load(&config_paths).unwrap_or_else(|err| {
         eprintln!("Problem parsing arguments: {err}");
         process::exit(1);
     });
 
     let (tx, rx): (Sender<CheckResult>, Receiver<CheckResult>) = mpsc::channel(RESULT_BUFFER_SIZE);
+    let (cmd_tx, cmd_rx): (Sender<BackendCommand>, Receiver<BackendCommand>) =
+        mpsc::channel(COMMAND_BUFFER_SIZE);
 
     let conf_clone = conf.clone();
     tokio::spawn(async move {
-        backend::run_backend(conf_clone, tx).await;
+        backend::run_backend(conf_clone, tx, cmd_rx).await;
     });
 
+    let (config_tx, config_rx): (Sender<ConfigEvent>, Receiver<ConfigEvent>) =
+        mpsc::channel(CONFIG_EVENT_BUFFER_SIZE);
+    tokio::spawn(watcher::watch_config(config_paths.clone(), config_tx));
+
+    let (notify_tx, notify_rx): (Sender<Notification>, Receiver<Notification>) =
+        mpsc::channel(NOTIFICATION_BUFFER_SIZE);
+    let (notifier_tx, notifier_rx): (Sender<NotifierEvent>, Receiver<NotifierEvent>) =
+        mpsc::channel(NOTIFICATION_BUFFER_SIZE);
+    let notifier = tokio::spawn(notify::run_notifier(notify_rx, notifier_tx));
+
+    let mut application = App::new(&conf, config_paths, notify_tx);
+
+    if let Some(metrics) = application.metrics() {
+        let listener = TcpListener::bind(conf.metrics.listen)
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("Couldn't serve metrics on {}: {err}", conf.metrics.listen);
+                process::exit(1);
+            });
+        tokio::spawn(metrics::serve(listener, metrics));
+    }
+
+    if let Some(api) = application.api() {
+        let token = conf.api.token.as_deref().map(interpolate_env).transpose();
+        let token = token.unwrap_or_else(|err| {
+            eprintln!("Couldn't read the API token: {err}");
+            process::exit(1);
+        });
+        let listener = TcpListener::bind(conf.api.listen)
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("Couldn't serve the API on {}: {err}", conf.api.listen);
+                process::exit(1);
+            });
+        tokio::spawn(api::serve(listener, api, token));
+    }
+
+    if cli.headless {
+        return headless::run(
+            &mut application,
+            rx,
+            cmd_tx,
+            config_rx,
+            notifier_rx,
+            notifier,
+        )
+        .await;
+    }
+
     let mut terminal = ratatui::init();
-    let mut application = App::new(&conf.endpoints);
-    let app_result = app::run_app(&mut application, &mut terminal, rx).await;
+    let app_result = app::run_app(
+        &mut application,
+        &mut terminal,
+        rx,
+        cmd_tx,
+        config_rx,
+        notifier_rx,
+    )
+    .await;
 
     ratatui::restore();
     app_result
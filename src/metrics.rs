@@ -0,0 +1,318 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+
+use axum::{Router, extract::State, http::header, response::IntoResponse, routing::get};
+use tokio::net::TcpListener;
+
+use crate::{
+    backend::{CheckResult, CheckStatus},
+    state::EndpointState,
+    stats::incidents::Health,
+};
+
+// Prometheus exporter.
+//
+// The App feeds every result it records into `Metrics`, so the exporter
+// always agrees with the TUI. The /metrics endpoint renders them in the
+// Prometheus text format when it's scraped.
+
+// Upper bounds of the latency histogram buckets in seconds
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The series of a single endpoint.
+#[derive(Debug)]
+struct EndpointMetrics {
+    url: String,
+    tags: Vec<String>,
+
+    up: bool,
+    // The latest HTTP status code, 0 when the request failed
+    status_code: u16,
+    checks: u64,
+    failures: u64,
+
+    // Cumulative counts per bucket, the +Inf bucket is `checks`
+    latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_sum: f64,
+}
+
+/// The metrics of every endpoint, shared with the HTTP server.
+#[derive(Default)]
+pub struct Metrics {
+    endpoints: Mutex<HashMap<String, EndpointMetrics>>,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Records a result right after it was recorded in `state`.
+    pub fn record(&self, state: &EndpointState, result: &CheckResult) {
+        let Ok(mut endpoints) = self.endpoints.lock() else {
+            return;
+        };
+
+        let metrics = endpoints
+            .entry(state.name.clone())
+            .or_insert_with(|| EndpointMetrics {
+                url: String::new(),
+                tags: Vec::new(),
+                up: false,
+                status_code: 0,
+                checks: 0,
+                failures: 0,
+                latency_buckets: [0; LATENCY_BUCKETS.len()],
+                latency_sum: 0.0,
+            });
+
+        // The url and tags can change on a config reload
+        metrics.url = state.url.clone();
+        metrics.tags = state.tags.clone();
+
+        let down = state.health == Some(Health::Down);
+        metrics.up = !down;
+        metrics.status_code = match result.status {
+            CheckStatus::Success { code, .. } => code,
+            CheckStatus::Error { .. } => 0,
+        };
+        metrics.checks += 1;
+        if down {
+            metrics.failures += 1;
+        }
+
+        let latency = result.latency.as_secs_f64();
+        metrics.latency_sum += latency;
+        for (count, bound) in metrics.latency_buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if latency <= bound {
+                *count += 1;
+            }
+        }
+    }
+
+    /// Drops the series of endpoints that no longer exist.
+    pub fn retain(&self, endpoints: &[String]) {
+        if let Ok(mut metrics) = self.endpoints.lock() {
+            metrics.retain(|name, _| endpoints.contains(name));
+        }
+    }
+
+    /// Renders every series in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let Ok(endpoints) = self.endpoints.lock() else {
+            return String::new();
+        };
+
+        let mut series: Vec<(String, &EndpointMetrics)> = endpoints
+            .iter()
+            .map(|(name, metrics)| (labels(name, metrics), metrics))
+            .collect();
+        series.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut out = String::new();
+
+        write_family(
+            &mut out,
+            "statui_up",
+            "gauge",
+            "Whether the endpoint is up (1) or down (0).",
+        );
+        for (labels, metrics) in &series {
+            let _ = writeln!(out, "statui_up{{{labels}}} {}", metrics.up as u8);
+        }
+
+        write_family(
+            &mut out,
+            "statui_latency_seconds",
+            "histogram",
+            "Latency of the checks.",
+        );
+        for (labels, metrics) in &series {
+            for (count, bound) in metrics.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "statui_latency_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "statui_latency_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                metrics.checks
+            );
+            let _ = writeln!(
+                out,
+                "statui_latency_seconds_sum{{{labels}}} {}",
+                metrics.latency_sum
+            );
+            let _ = writeln!(
+                out,
+                "statui_latency_seconds_count{{{labels}}} {}",
+                metrics.checks
+            );
+        }
+
+        write_family(
+            &mut out,
+            "statui_http_status_code",
+            "gauge",
+            "HTTP status code of the latest check, 0 if the request failed.",
+        );
+        for (labels, metrics) in &series {
+            let _ = writeln!(
+                out,
+                "statui_http_status_code{{{labels}}} {}",
+                metrics.status_code
+            );
+        }
+
+        write_family(
+            &mut out,
+            "statui_checks_total",
+            "counter",
+            "Number of checks.",
+        );
+        for (labels, metrics) in &series {
+            let _ = writeln!(out, "statui_checks_total{{{labels}}} {}", metrics.checks);
+        }
+
+        write_family(
+            &mut out,
+            "statui_failures_total",
+            "counter",
+            "Number of checks that found the endpoint down.",
+        );
+        for (labels, metrics) in &series {
+            let _ = writeln!(
+                out,
+                "statui_failures_total{{{labels}}} {}",
+                metrics.failures
+            );
+        }
+
+        out
+    }
+}
+
+/// Serves /metrics on `listener` until the app exits.
+pub async fn serve(listener: TcpListener, metrics: Arc<Metrics>) {
+    let app = Router::new()
+        .route("/metrics", get(scrape))
+        .with_state(metrics);
+
+    let _ = axum::serve(listener, app).await;
+}
+
+async fn scrape(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], metrics.render())
+}
+
+fn write_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn labels(name: &str, metrics: &EndpointMetrics) -> String {
+    format!(
+        "endpoint=\"{}\",url=\"{}\",tags=\"{}\"",
+        escape(name),
+        escape(&metrics.url),
+        escape(&metrics.tags.join(","))
+    )
+}
+
+/// Escapes a label value as the text format expects.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Endpoint, StatuiConfig};
+    use std::time::{Duration, SystemTime};
+
+    fn check(metrics: &Metrics, state: &mut EndpointState, status: CheckStatus, latency_ms: u64) {
+        let result = CheckResult {
+            name: state.name.clone(),
+            status,
+            latency: Duration::from_millis(latency_ms),
+            cert_expiry: None,
+            generation: 0,
+        };
+        state.record(SystemTime::now(), result.clone(), &StatuiConfig::default());
+        metrics.record(state, &result);
+    }
+
+    #[test]
+    fn renders_the_text_format() {
+        let endpoint = Endpoint {
+            name: "api \"eu\"".to_string(),
+            url: "http://localhost".to_string(),
+            tags: vec!["prod".to_string(), "eu".to_string()],
+            ..Endpoint::default()
+        };
+        let mut state = EndpointState::new(&endpoint, &StatuiConfig::default());
+        let metrics = Metrics::default();
+
+        let ok = CheckStatus::Success {
+            code: 200,
+            text: "OK".to_string(),
+        };
+        check(&metrics, &mut state, ok, 20);
+        let timeout = CheckStatus::Error {
+            message: "Timeout".to_string(),
+        };
+        check(&metrics, &mut state, timeout, 3000);
+
+        let out = metrics.render();
+        let labels = r#"endpoint="api \"eu\"",url="http://localhost",tags="prod,eu""#;
+        for line in [
+            "# TYPE statui_up gauge".to_string(),
+            format!("statui_up{{{labels}}} 0"),
+            format!("statui_http_status_code{{{labels}}} 0"),
+            format!("statui_checks_total{{{labels}}} 2"),
+            format!("statui_failures_total{{{labels}}} 1"),
+            format!("statui_latency_seconds_bucket{{{labels},le=\"0.01\"}} 0"),
+            format!("statui_latency_seconds_bucket{{{labels},le=\"0.025\"}} 1"),
+            format!("statui_latency_seconds_bucket{{{labels},le=\"5\"}} 2"),
+            format!("statui_latency_seconds_bucket{{{labels},le=\"+Inf\"}} 2"),
+            format!("statui_latency_seconds_sum{{{labels}}} 3.02"),
+            format!("statui_latency_seconds_count{{{labels}}} 2"),
+        ] {
+            assert!(out.lines().any(|l| l == line), "missing {line} in\n{out}");
+        }
+    }
+
+    #[test]
+    fn drops_removed_endpoints() {
+        let metrics = Metrics::default();
+        for name in ["api", "web"] {
+            let endpoint = Endpoint {
+                name: name.to_string(),
+                url: "http://localhost".to_string(),
+                ..Endpoint::default()
+            };
+            let mut state = EndpointState::new(&endpoint, &StatuiConfig::default());
+            let ok = CheckStatus::Success {
+                code: 200,
+                text: "OK".to_string(),
+            };
+            check(&metrics, &mut state, ok, 20);
+        }
+
+        metrics.retain(&["web".to_string()]);
+        let out = metrics.render();
+        assert!(!out.contains("endpoint=\"api\""));
+        assert!(out.contains("statui_up{endpoint=\"web\",url=\"http://localhost\",tags=\"\"} 1"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+
+use crate::{
+    config::{NotificationConfig, SmtpTls, interpolate_env},
+    notify::{SendError, Transition},
+};
+
+// Email notifications. Transitions are batched by the notifier, so one
+// email can be a digest of several endpoints changing state.
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Sends the transitions as a single email.
+///
+/// Permanent SMTP errors (like a rejected address or bad credentials)
+/// aren't worth retrying, anything else is.
+pub async fn send(
+    channel: &NotificationConfig,
+    transitions: &[Transition],
+) -> Result<(), SendError> {
+    let message = build_message(channel, transitions).map_err(permanent)?;
+    let transport = build_transport(channel).map_err(permanent)?;
+
+    transport
+        .send(message)
+        .await
+        .map(|_| ())
+        .map_err(|err| SendError {
+            message: format!("SMTP error: {err}"),
+            retryable: !err.is_permanent(),
+        })
+}
+
+fn build_transport(
+    channel: &NotificationConfig,
+) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let Some(smtp) = &channel.smtp else {
+        return Err("no smtp server configured".to_string());
+    };
+
+    let mut builder = match smtp.tls {
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host),
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host),
+        SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            &smtp.host,
+        )),
+    }
+    .map_err(|err| format!("invalid smtp host: {err}"))?
+    .timeout(Some(SMTP_TIMEOUT));
+
+    if let Some(port) = smtp.port {
+        builder = builder.port(port);
+    }
+
+    if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+        builder = builder.credentials(Credentials::new(
+            interpolate_env(username)?,
+            interpolate_env(password)?,
+        ));
+    }
+
+    Ok(builder.build())
+}
+
+fn build_message(
+    channel: &NotificationConfig,
+    transitions: &[Transition],
+) -> Result<Message, String> {
+    let parse = |address: &str| {
+        address
+            .parse::<Mailbox>()
+            .map_err(|err| format!("invalid address '{address}': {err}"))
+    };
+
+    let Some(from) = &channel.from else {
+        return Err("no from address configured".to_string());
+    };
+    if channel.to.is_empty() {
+        return Err("no to addresses configured".to_string());
+    }
+
+    let mut builder = Message::builder()
+        .from(parse(from)?)
+        .subject(subject(transitions))
+        .header(ContentType::TEXT_PLAIN);
+
+    for to in &channel.to {
+        builder = builder.to(parse(to)?);
+    }
+
+    let body: Vec<String> = transitions
+        .iter()
+        .map(|transition| match &channel.template {
+            Some(template) => transition.render(template, str::to_string),
+            None => transition.default_message(),
+        })
+        .collect();
+
+    builder
+        .body(body.join("\n"))
+        .map_err(|err| format!("couldn't build the email: {err}"))
+}
+
+fn subject(transitions: &[Transition]) -> String {
+    match transitions {
+        [transition] => format!(
+            "[statui] {} is {}",
+            transition.endpoint,
+            transition.to.label()
+        ),
+        _ => format!("[statui] {} endpoint state changes", transitions.len()),
+    }
+}
+
+fn permanent(message: String) -> SendError {
+    SendError {
+        message,
+        retryable: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::incidents::Health;
+    use std::time::SystemTime;
+
+    fn transition(endpoint: &str, to: Health) -> Transition {
+        Transition {
+            endpoint: endpoint.to_string(),
+            url: "https://api.example.com".to_string(),
+            from: Some(Health::Up),
+            to,
+            status: "Timeout".to_string(),
+            error: "Timeout".to_string(),
+            latency: Duration::from_millis(42),
+            duration: Duration::from_secs(60),
+            at: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn digests_several_transitions() {
+        let channel: NotificationConfig = toml::from_str(
+            r#"
+            name = "mail"
+            kind = "email"
+            from = "statui@example.com"
+            to = ["ops@example.com"]
+            template = "{{endpoint}} is {{status}}"
+            "#,
+        )
+        .unwrap();
+
+        let single = [transition("api", Health::Down)];
+        assert_eq!(subject(&single), "[statui] api is DOWN");
+
+        let digest = [
+            transition("api", Health::Down),
+            transition("web", Health::Degraded),
+        ];
+        assert_eq!(subject(&digest), "[statui] 2 endpoint state changes");
+
+        let message = build_message(&channel, &digest).unwrap();
+        let raw = String::from_utf8(message.formatted()).unwrap();
+        assert!(raw.contains("api is DOWN\r\nweb is DEGRADED"), "{raw}");
+    }
+
+    #[test]
+    fn rejects_bad_addresses() {
+        let channel: NotificationConfig = toml::from_str(
+            r#"
+            name = "mail"
+            kind = "email"
+            from = "statui@example.com"
+            to = ["not an address"]
+            "#,
+        )
+        .unwrap();
+
+        let err = build_message(&channel, &[transition("api", Health::Down)]).unwrap_err();
+        assert!(err.starts_with("invalid address 'not an address'"), "{err}");
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use tokio::{io::AsyncWriteExt, process::Command, sync::Semaphore, time::timeout};
+
+use crate::{notify::Transition, stats::incidents::Health};
+
+// Command hooks. The command runs through the shell with the details of the
+// transition in its environment and as JSON on stdin, it's killed if it
+// runs past its timeout. On unix it runs in its own process group so the
+// commands started by the shell are killed along with it.
+
+/// A command to run for a transition.
+#[derive(Debug, Clone)]
+pub struct HookCommand {
+    pub command: String,
+    pub timeout: Duration,
+    pub max_concurrent: usize,
+}
+
+/// How a hook run ended.
+#[derive(Debug, Clone)]
+pub enum HookResult {
+    Exited { code: Option<i32>, stderr: String },
+    TimedOut,
+    Failed(String),
+}
+
+/// A finished hook run, shown in the inspector log of its endpoint.
+#[derive(Debug, Clone)]
+pub struct HookOutcome {
+    pub endpoint: String,
+    pub event: &'static str,
+    pub at: SystemTime,
+    pub duration: Duration,
+    pub result: HookResult,
+}
+
+impl HookOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self.result, HookResult::Exited { code: Some(0), .. })
+    }
+
+    pub fn describe(&self) -> String {
+        match &self.result {
+            HookResult::Exited { code, stderr } => {
+                let code = match code {
+                    Some(code) => code.to_string(),
+                    None => "by signal".to_string(),
+                };
+                match stderr.lines().last() {
+                    Some(line) if !self.is_success() => {
+                        format!("{} exited {code}: {line}", self.event)
+                    }
+                    _ => format!("{} exited {code}", self.event),
+                }
+            }
+            HookResult::TimedOut => format!("{} timed out", self.event),
+            HookResult::Failed(err) => format!("{} failed: {err}", self.event),
+        }
+    }
+}
+
+/// The name of the hook that runs when an endpoint becomes `health`.
+pub fn event_name(health: Health) -> &'static str {
+    match health {
+        Health::Up => "on_up",
+        Health::Degraded => "on_degraded",
+        Health::Down => "on_down",
+    }
+}
+
+/// Runs the hook once a slot on `semaphore` frees up.
+pub async fn run(
+    hook: HookCommand,
+    transition: Transition,
+    semaphore: Arc<Semaphore>,
+) -> HookOutcome {
+    let _permit = semaphore.acquire_owned().await;
+
+    let started = Instant::now();
+    let result = match execute(&hook.command, &transition, hook.timeout).await {
+        Ok(Some((code, stderr))) => HookResult::Exited { code, stderr },
+        Ok(None) => HookResult::TimedOut,
+        Err(err) => HookResult::Failed(err.to_string()),
+    };
+
+    HookOutcome {
+        endpoint: transition.endpoint.clone(),
+        event: event_name(transition.to),
+        at: SystemTime::now(),
+        duration: started.elapsed(),
+        result,
+    }
+}
+
+/// Runs the command, returns None if it timed out.
+async fn execute(
+    command: &str,
+    transition: &Transition,
+    limit: Duration,
+) -> std::io::Result<Option<(Option<i32>, String)>> {
+    let mut child = shell(command)
+        .env("STATUI_ENDPOINT", &transition.endpoint)
+        .env("STATUI_URL", &transition.url)
+        .env("STATUI_STATUS", transition.to.label())
+        .env("STATUI_PREVIOUS", transition.previous_label())
+        .env("STATUI_CHECK", &transition.status)
+        .env("STATUI_ERROR", &transition.error)
+        .env(
+            "STATUI_LATENCY_MS",
+            transition.latency.as_millis().to_string(),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    // Also covers statui shutting down while the hook is still running
+    let mut group = ProcessGroup(child.id());
+
+    let run = async {
+        if let Some(mut stdin) = child.stdin.take() {
+            // A hook that doesn't read its stdin closes it early, that's fine
+            let _ = stdin
+                .write_all(transition.to_json().to_string().as_bytes())
+                .await;
+        }
+        child.wait_with_output().await
+    };
+
+    let Ok(output) = timeout(limit, run).await else {
+        return Ok(None);
+    };
+    // What the hook left running in the background on purpose stays up
+    group.0 = None;
+
+    let output = output?;
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+    Ok(Some((output.status.code(), stderr)))
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command).process_group(0);
+    cmd
+}
+
+/// Kills the process group of a hook that's dropped before it exits.
+struct ProcessGroup(Option<u32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Some(pid) = self.0 {
+            kill_process_group(pid);
+        }
+    }
+}
+
+// cmd is killed by `kill_on_drop`, there's no group to go after
+#[cfg(windows)]
+fn kill_process_group(_pid: u32) {}
+
+#[cfg(not(windows))]
+fn kill_process_group(pid: u32) {
+    // The group was created with the shell's pid as its id
+    let Ok(pgid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+    // SAFETY: killpg only sends a signal, a group that's already gone is an error we ignore
+    unsafe {
+        libc::killpg(pgid, libc::SIGKILL);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn transition() -> Transition {
+        Transition {
+            endpoint: "api".to_string(),
+            url: "https://api.example.com".to_string(),
+            from: Some(Health::Up),
+            to: Health::Down,
+            status: "Timeout".to_string(),
+            error: "Timeout".to_string(),
+            latency: Duration::from_millis(42),
+            duration: Duration::from_secs(60),
+            at: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn hook(command: &str, timeout: Duration) -> HookCommand {
+        HookCommand {
+            command: command.to_string(),
+            timeout,
+            max_concurrent: 1,
+        }
+    }
+
+    // Zombies count as gone, nobody might be around to reap them
+    fn is_running(pid: &str) -> bool {
+        std::fs::read_to_string(format!("/proc/{pid}/stat"))
+            .is_ok_and(|stat| !stat.contains(") Z "))
+    }
+
+    #[tokio::test]
+    async fn passes_the_transition_to_the_command() {
+        let command = r#"echo "$STATUI_ENDPOINT $STATUI_STATUS $STATUI_LATENCY_MS" >&2; exit 3"#;
+        let slots = Arc::new(Semaphore::new(1));
+        let outcome = run(hook(command, Duration::from_secs(10)), transition(), slots).await;
+
+        assert_eq!(outcome.event, "on_down");
+        assert!(!outcome.is_success());
+        assert_eq!(outcome.describe(), "on_down exited 3: api DOWN 42");
+    }
+
+    #[tokio::test]
+    async fn kills_the_whole_group_on_timeout() {
+        let pid_file = std::env::temp_dir().join(format!("statui-hook-{}", std::process::id()));
+        let command = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let slots = Arc::new(Semaphore::new(1));
+
+        let started = Instant::now();
+        let outcome = run(
+            hook(&command, Duration::from_millis(500)),
+            transition(),
+            slots,
+        )
+        .await;
+        assert!(matches!(outcome.result, HookResult::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let _ = std::fs::remove_file(&pid_file);
+        let pid = pid.trim();
+
+        // SIGKILL is delivered asynchronously
+        for _ in 0..50 {
+            if !is_running(pid) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("the background sleep {pid} survived the timeout");
+    }
+}
@@ -0,0 +1,440 @@
+pub mod email;
+pub mod hook;
+pub mod webhook;
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Local};
+use serde_json::json;
+use tokio::{
+    sync::{
+        Semaphore,
+        mpsc::{Receiver, Sender},
+    },
+    task::JoinSet,
+    time::{Instant, sleep, sleep_until},
+};
+
+use crate::{
+    config::{NotificationConfig, NotificationKind},
+    notify::hook::{HookCommand, HookOutcome},
+    state::EndpointState,
+    stats::incidents::{self, Health},
+    ui::util,
+};
+
+// Notifications are sent from their own task so a slow or unreachable
+// channel never holds up the UI. Each delivery is retried on its own with
+// an exponential backoff, and deliveries that ran out of retries are
+// reported back to the UI.
+//
+// Email channels collect the transitions for a short while and send them
+// as one digest, so a network blip doesn't flood anyone's inbox.
+//
+// Command hooks run from here as well, their outcomes are sent back to the
+// UI so they show up in the inspector log.
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// An endpoint going from one health to another.
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub endpoint: String,
+    pub url: String,
+    // None when there's no earlier health, e.g. down from the first check
+    pub from: Option<Health>,
+    pub to: Health,
+
+    // The status of the check that caused the transition (e.g. "200 OK")
+    pub status: String,
+    // Why the endpoint isn't up, empty when it recovered
+    pub error: String,
+    pub latency: Duration,
+
+    // How long the endpoint was in the previous state
+    pub duration: Duration,
+    pub at: SystemTime,
+}
+
+impl Transition {
+    pub fn new(
+        state: &EndpointState,
+        from: Option<Health>,
+        from_since: SystemTime,
+        at: SystemTime,
+    ) -> Option<Self> {
+        let to = state.health?;
+        let (status, latency) = state
+            .recent_checks
+            .front()
+            .map(|(_, result, _)| (incidents::describe(&result.status), result.latency))?;
+
+        Some(Transition {
+            endpoint: state.name.clone(),
+            url: state.url.clone(),
+            from,
+            to,
+            status,
+            error: if to == Health::Up {
+                String::new()
+            } else {
+                state.health_reason.clone()
+            },
+            latency,
+            duration: at.duration_since(from_since).unwrap_or_default(),
+            at,
+        })
+    }
+
+    /// Fills the {{placeholders}} of a template.
+    ///
+    /// `escape` is applied to every value, e.g. to keep JSON templates valid.
+    pub fn render(&self, template: &str, escape: impl Fn(&str) -> String) -> String {
+        let at: DateTime<Local> = self.at.into();
+        let values = [
+            ("endpoint", self.endpoint.clone()),
+            ("url", self.url.clone()),
+            ("status", self.to.label().to_string()),
+            ("previous", self.previous_label().to_string()),
+            ("check", self.status.clone()),
+            ("error", self.error.clone()),
+            ("latency", format!("{}ms", self.latency.as_millis())),
+            ("duration", util::format_duration(self.duration)),
+            ("time", at.to_rfc3339()),
+        ]
+        .map(|(key, value)| (key, escape(&value)));
+
+        util::fill_placeholders(template, &values)
+    }
+
+    /// The label of the previous health, UNKNOWN if there was none.
+    pub fn previous_label(&self) -> &'static str {
+        self.from.map_or("UNKNOWN", |from| from.label())
+    }
+
+    /// The transition as JSON, used by webhooks and hooks.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "endpoint": self.endpoint,
+            "url": self.url,
+            "status": self.to,
+            "previous": self.from,
+            "check": self.status,
+            "error": self.error,
+            "latency_ms": self.latency.as_millis() as u64,
+            "duration_secs": self.duration.as_secs(),
+        })
+    }
+
+    /// The message used when a channel doesn't have its own template.
+    pub fn default_message(&self) -> String {
+        let template = match self.to {
+            Health::Up => "{{endpoint}} is UP again after being {{previous}} for {{duration}}",
+            Health::Degraded | Health::Down => {
+                "{{endpoint}} is {{status}}: {{error}} ({{latency}})"
+            }
+        };
+        self.render(template, str::to_string)
+    }
+}
+
+/// A transition along with the channels it should be sent to
+/// and the hook to run for it.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub transition: Transition,
+    pub channels: Vec<NotificationConfig>,
+    pub hook: Option<HookCommand>,
+}
+
+/// What the notifier reports back to the UI.
+#[derive(Debug, Clone)]
+pub enum NotifierEvent {
+    DeliveryFailed(DeliveryFailure),
+    HookFinished(HookOutcome),
+}
+
+/// A notification that couldn't be delivered even after retrying.
+#[derive(Debug, Clone)]
+pub struct DeliveryFailure {
+    pub channel: String,
+    // The endpoints the notification was about
+    pub endpoint: String,
+    pub error: String,
+}
+
+/// Why a delivery failed and whether it's worth trying again.
+pub struct SendError {
+    pub message: String,
+    pub retryable: bool,
+}
+
+/// What a single delivery sends.
+enum Payload {
+    Webhook(Transition),
+    Email(Vec<Transition>),
+}
+
+/// Transitions waiting to be sent to an email channel.
+struct Batch {
+    channel: NotificationConfig,
+    transitions: Vec<Transition>,
+    send_at: Instant,
+}
+
+/// Sends the notifications coming in on `rx` until the channel closes.
+///
+/// Once it closes the email batches still waiting are sent right away, and
+/// it returns after every delivery and hook run is done.
+pub async fn run_notifier(mut rx: Receiver<Notification>, event_tx: Sender<NotifierEvent>) {
+    let client = webhook::client();
+    let mut batches: HashMap<String, Batch> = HashMap::new();
+    // Deliveries and hook runs in flight
+    let mut tasks = JoinSet::new();
+
+    // Shared by all the hooks, its permits follow the limit in the config
+    let mut hook_limit = 0;
+    let hook_slots = Arc::new(Semaphore::new(0));
+
+    loop {
+        let next_batch = batches.values().map(|batch| batch.send_at).min();
+
+        tokio::select! {
+            notification = rx.recv() => {
+                let Some(notification) = notification else {
+                    break;
+                };
+
+                if let Some(hook) = notification.hook {
+                    let limit = hook.max_concurrent.max(1);
+                    resize_hook_slots(&hook_slots, hook_limit, limit);
+                    hook_limit = limit;
+
+                    let transition = notification.transition.clone();
+                    let slots = hook_slots.clone();
+                    let event_tx = event_tx.clone();
+                    tasks.spawn(async move {
+                        let outcome = hook::run(hook, transition, slots).await;
+                        let _ = event_tx.send(NotifierEvent::HookFinished(outcome)).await;
+                    });
+                }
+
+                for channel in notification.channels {
+                    if !channel.on.is_empty() && !channel.on.contains(&notification.transition.to) {
+                        continue;
+                    }
+
+                    let transition = notification.transition.clone();
+                    if channel.kind == NotificationKind::Email {
+                        let batch = batches.entry(channel.name.clone()).or_insert_with(|| Batch {
+                            send_at: Instant::now() + Duration::from_secs(channel.batch_secs),
+                            channel,
+                            transitions: Vec::new(),
+                        });
+                        batch.transitions.push(transition);
+                    } else {
+                        let payload = Payload::Webhook(transition);
+                        tasks.spawn(deliver(client.clone(), channel, payload, event_tx.clone()));
+                    }
+                }
+            }
+            _ = sleep_until(next_batch.unwrap_or_else(Instant::now)), if next_batch.is_some() => {
+                let now = Instant::now();
+                let due: Vec<String> = batches
+                    .iter()
+                    .filter(|(_, batch)| batch.send_at <= now)
+                    .map(|(name, _)| name.clone())
+                    .collect();
+
+                for batch in due.iter().filter_map(|name| batches.remove(name)) {
+                    let payload = Payload::Email(batch.transitions);
+                    tasks.spawn(deliver(client.clone(), batch.channel, payload, event_tx.clone()));
+                }
+            }
+            Some(_) = tasks.join_next(), if !tasks.is_empty() => {}
+        }
+    }
+
+    for (_, batch) in batches {
+        let payload = Payload::Email(batch.transitions);
+        tasks.spawn(deliver(
+            client.clone(),
+            batch.channel,
+            payload,
+            event_tx.clone(),
+        ));
+    }
+    while tasks.join_next().await.is_some() {}
+}
+
+/// Grows or shrinks the hook slots from `from` to `to` permits.
+///
+/// Slots held by running hooks can't be taken back, they're forgotten
+/// as soon as those hooks finish.
+fn resize_hook_slots(slots: &Arc<Semaphore>, from: usize, to: usize) {
+    if to > from {
+        slots.add_permits(to - from);
+        return;
+    }
+
+    let excess = from - to;
+    let owed = excess - slots.forget_permits(excess);
+    if owed > 0 {
+        let slots = slots.clone();
+        tokio::spawn(async move {
+            if let Ok(permits) = slots.acquire_many_owned(owed as u32).await {
+                permits.forget();
+            }
+        });
+    }
+}
+
+async fn deliver(
+    client: reqwest::Client,
+    channel: NotificationConfig,
+    payload: Payload,
+    event_tx: Sender<NotifierEvent>,
+) {
+    let mut attempt = 0;
+
+    loop {
+        let result = match &payload {
+            Payload::Webhook(transition) => webhook::send(&client, &channel, transition).await,
+            Payload::Email(transitions) => email::send(&channel, transitions).await,
+        };
+        let Err(err) = result else {
+            return;
+        };
+
+        if !err.retryable || attempt >= channel.retries {
+            let endpoints: Vec<&str> = match &payload {
+                Payload::Webhook(transition) => vec![transition.endpoint.as_str()],
+                Payload::Email(transitions) => {
+                    transitions.iter().map(|t| t.endpoint.as_str()).collect()
+                }
+            };
+
+            let failure = DeliveryFailure {
+                channel: channel.name.clone(),
+                endpoint: endpoints.join(", "),
+                error: err.message,
+            };
+            let _ = event_tx.send(NotifierEvent::DeliveryFailed(failure)).await;
+            return;
+        }
+
+        sleep(retry_backoff(attempt)).await;
+        attempt += 1;
+    }
+}
+
+/// How long to wait before retrying after the `attempt`th try failed,
+/// doubling every time up to MAX_BACKOFF.
+fn retry_backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    fn transition() -> Transition {
+        Transition {
+            endpoint: "api".to_string(),
+            url: "https://api.example.com".to_string(),
+            from: Some(Health::Up),
+            to: Health::Down,
+            status: "500 Internal Server Error".to_string(),
+            error: "Got \"{{url}}\" back".to_string(),
+            latency: Duration::from_millis(42),
+            duration: Duration::from_secs(2 * 60 * 60 + 5 * 60),
+            at: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn renders_templates() {
+        let text = transition().render(
+            "{{endpoint}} is {{status}} (was {{previous}} for {{duration}}): {{check}} in {{latency}}",
+            str::to_string,
+        );
+        assert_eq!(
+            text,
+            "api is DOWN (was UP for 2h 5m): 500 Internal Server Error in 42ms"
+        );
+    }
+
+    #[test]
+    fn escapes_values_but_not_the_template() {
+        let text = transition().render(r#"{"error": "{{error}}", "{{unknown}}": 1}"#, |value| {
+            value.replace('"', "\\\"")
+        });
+        assert_eq!(
+            text,
+            r#"{"error": "Got \"{{url}}\" back", "{{unknown}}": 1}"#
+        );
+    }
+
+    #[test]
+    fn unknown_previous_health() {
+        let transition = Transition {
+            from: None,
+            ..transition()
+        };
+        assert_eq!(transition.render("{{previous}}", str::to_string), "UNKNOWN");
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        let backoffs: Vec<u64> = (0..8).map(|n| retry_backoff(n).as_secs()).collect();
+        assert_eq!(backoffs, [1, 2, 4, 8, 16, 32, 60, 60]);
+        assert_eq!(retry_backoff(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn batches_emails_per_channel() {
+        // No smtp server so the delivery fails right away, without retrying
+        let channel: NotificationConfig = toml::from_str(
+            r#"
+            name = "mail"
+            kind = "email"
+            batch_secs = 3600
+            retries = 5
+            "#,
+        )
+        .unwrap();
+
+        let (tx, rx) = mpsc::channel(8);
+        let (event_tx, mut event_rx) = mpsc::channel(8);
+        for endpoint in ["api", "web"] {
+            let notification = Notification {
+                transition: Transition {
+                    endpoint: endpoint.to_string(),
+                    ..transition()
+                },
+                channels: vec![channel.clone()],
+                hook: None,
+            };
+            tx.send(notification).await.unwrap();
+        }
+        // Closing the channel sends the waiting batches right away
+        drop(tx);
+        run_notifier(rx, event_tx).await;
+
+        let Some(NotifierEvent::DeliveryFailed(failure)) = event_rx.recv().await else {
+            panic!("expected a delivery failure");
+        };
+        assert_eq!(failure.channel, "mail");
+        assert_eq!(failure.endpoint, "api, web");
+        assert_eq!(failure.error, "no from address configured");
+        assert!(event_rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::{
+    config::{NotificationConfig, NotificationKind, interpolate_env},
+    notify::{SendError, Transition},
+    stats::incidents::Health,
+};
+
+// HTTP based notification channels. They all boil down to a POST, they
+// only differ in the shape of the body.
+
+static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(APP_USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("Failed to build reqwest client")
+}
+
+/// Sends a single notification to `channel`.
+///
+/// Connection errors, rate limits and server errors can be retried,
+/// other errors (like a wrong URL, a header that isn't valid or a rejected
+/// payload) can't.
+pub async fn send(
+    client: &reqwest::Client,
+    channel: &NotificationConfig,
+    transition: &Transition,
+) -> Result<(), SendError> {
+    let Some(url) = &channel.url else {
+        return Err(SendError {
+            message: "no url configured".to_string(),
+            retryable: false,
+        });
+    };
+    let url = interpolate_env(url).map_err(|message| SendError {
+        message,
+        retryable: false,
+    })?;
+
+    let message = match &channel.template {
+        Some(template) if channel.kind != NotificationKind::Webhook => {
+            transition.render(template, str::to_string)
+        }
+        _ => transition.default_message(),
+    };
+    let title = format!(
+        "statui: {} is {}",
+        transition.endpoint,
+        transition.to.label()
+    );
+
+    let mut request = client.post(&url);
+    request = match channel.kind {
+        NotificationKind::Webhook => match &channel.template {
+            Some(template) => request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(transition.render(template, json_escape)),
+            None => {
+                let mut body = transition.to_json();
+                body["message"] = message.into();
+                request.json(&body)
+            }
+        },
+        NotificationKind::Slack => request.json(&json!({ "text": message })),
+        NotificationKind::Discord => request.json(&json!({ "content": message })),
+        NotificationKind::Ntfy => request
+            .header("Title", encode_header_word(&title))
+            .header("Priority", ntfy_priority(transition.to))
+            .body(message),
+        NotificationKind::Gotify => request.json(&json!({
+            "title": title,
+            "message": message,
+            "priority": gotify_priority(transition.to),
+        })),
+        NotificationKind::Email => {
+            return Err(SendError {
+                message: "email channels aren't sent over http".to_string(),
+                retryable: false,
+            });
+        }
+    };
+
+    for (key, value) in &channel.headers {
+        let value = interpolate_env(value).map_err(|message| SendError {
+            message,
+            retryable: false,
+        })?;
+        request = request.header(key, value);
+    }
+
+    // Building the request first keeps invalid urls and headers from
+    // being mistaken for a network error and retried.
+    let request = request.build().map_err(|err| SendError {
+        message: format!("invalid request: {err}"),
+        retryable: false,
+    })?;
+
+    let response = client.execute(request).await.map_err(|err| SendError {
+        message: format!("couldn't reach it: {err}"),
+        retryable: true,
+    })?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    Err(SendError {
+        message: format!("answered with {status}"),
+        retryable: status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+    })
+}
+
+/// Escapes a value so it can be put inside a JSON string.
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// RFC 2047 encodes a header value that isn't plain ASCII (e.g. an endpoint
+/// name with accents), ntfy decodes it back.
+fn encode_header_word(value: &str) -> String {
+    if value.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+        return value.to_string();
+    }
+
+    let mut encoded = String::from("=?UTF-8?Q?");
+    for byte in value.bytes() {
+        match byte {
+            b' ' => encoded.push('_'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'!' | b'*' | b'+' | b'-' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("={byte:02X}")),
+        }
+    }
+    encoded.push_str("?=");
+    encoded
+}
+
+fn ntfy_priority(health: Health) -> &'static str {
+    match health {
+        Health::Down => "high",
+        Health::Degraded => "default",
+        Health::Up => "low",
+    }
+}
+
+fn gotify_priority(health: Health) -> u8 {
+    match health {
+        Health::Down => 8,
+        Health::Degraded => 5,
+        Health::Up => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    #[test]
+    fn encodes_non_ascii_titles() {
+        assert_eq!(
+            encode_header_word("statui: api is DOWN"),
+            "statui: api is DOWN"
+        );
+        assert_eq!(
+            encode_header_word("statui: café is UP"),
+            "=?UTF-8?Q?statui=3A_caf=C3=A9_is_UP?="
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_headers_arent_retried() {
+        // The url is never reached, the request fails to build first
+        let channel: NotificationConfig = toml::from_str(
+            r#"
+            name = "ntfy"
+            kind = "ntfy"
+            url = "http://127.0.0.1:9"
+            headers = { "Bad Header" = "1" }
+            "#,
+        )
+        .unwrap();
+        let transition = Transition {
+            endpoint: "café".to_string(),
+            url: "https://api.example.com".to_string(),
+            from: Some(Health::Up),
+            to: Health::Down,
+            status: "Timeout".to_string(),
+            error: "Timeout".to_string(),
+            latency: Duration::from_millis(42),
+            duration: Duration::from_secs(60),
+            at: SystemTime::UNIX_EPOCH,
+        };
+
+        let err = send(&client(), &channel, &transition).await.unwrap_err();
+        assert!(!err.retryable, "{}", err.message);
+        assert!(err.message.starts_with("invalid request"));
+    }
+}
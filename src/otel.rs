@@ -0,0 +1,369 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::{Result, eyre::eyre};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::{
+    KeyValue,
+    metrics::{Gauge, Histogram, Meter, MeterProvider},
+    trace::{Span, SpanKind, Status, Tracer, TracerProvider},
+};
+use opentelemetry_otlp::{
+    MetricExporter, SpanExporter, WithExportConfig, WithHttpConfig, WithTonicConfig,
+    tonic_types::{metadata::MetadataMap, transport::ClientTlsConfig},
+};
+use opentelemetry_sdk::{
+    Resource,
+    metrics::{PeriodicReader, SdkMeterProvider},
+    trace::{SdkTracer, SdkTracerProvider},
+};
+
+use crate::{
+    backend::{CheckResult, CheckStatus},
+    config::{OtlpConfig, OtlpProtocol, interpolate_env},
+    state::EndpointState,
+    stats::incidents::Health,
+};
+
+// OpenTelemetry exporter.
+//
+// Like the Prometheus exporter, the App feeds it every result it records.
+// Each check becomes a span and updates the up gauge and the latency
+// histogram, the SDK batches them and exports them over OTLP in the
+// background.
+
+const SCOPE: &str = "statui";
+
+// Upper bounds of the latency histogram buckets in seconds, the same as
+// the Prometheus exporter.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+struct Instruments {
+    up: Gauge<u64>,
+    latency: Histogram<f64>,
+}
+
+/// The OTLP trace and metric pipelines, flushed when dropped.
+pub struct Telemetry {
+    tracer: Option<(SdkTracerProvider, SdkTracer)>,
+    meter: Option<(SdkMeterProvider, Instruments)>,
+}
+
+impl Telemetry {
+    /// Builds the exporters, returns None if OTLP export is disabled.
+    ///
+    /// Needs to be called from within the tokio runtime for gRPC.
+    pub fn new(config: &OtlpConfig) -> Result<Option<Self>> {
+        if !config.enabled || !(config.traces || config.metrics) {
+            return Ok(None);
+        }
+
+        let resource = Resource::builder()
+            .with_service_name(SCOPE)
+            .with_attributes(
+                config
+                    .resource_attributes
+                    .iter()
+                    .map(|(key, value)| KeyValue::new(key.clone(), value.clone())),
+            )
+            .build();
+
+        let tracer = if config.traces {
+            let provider = SdkTracerProvider::builder()
+                .with_batch_exporter(span_exporter(config)?)
+                .with_resource(resource.clone())
+                .build();
+            let tracer = provider.tracer(SCOPE);
+            Some((provider, tracer))
+        } else {
+            None
+        };
+
+        let meter = if config.metrics {
+            let reader = PeriodicReader::builder(metric_exporter(config)?)
+                .with_interval(Duration::from_secs(config.export_interval.max(1)))
+                .build();
+            let provider = SdkMeterProvider::builder()
+                .with_reader(reader)
+                .with_resource(resource)
+                .build();
+
+            let instruments = instruments(&provider.meter(SCOPE));
+            Some((provider, instruments))
+        } else {
+            None
+        };
+
+        Ok(Some(Telemetry { tracer, meter }))
+    }
+
+    /// Records a result that came in at `at` right after it was recorded in `state`.
+    pub fn record(&self, state: &EndpointState, at: SystemTime, result: &CheckResult) {
+        let attributes = [
+            KeyValue::new("statui.endpoint", state.name.clone()),
+            KeyValue::new("url.full", state.url.clone()),
+            KeyValue::new("statui.tags", state.tags.join(",")),
+        ];
+        let health = state.health.unwrap_or(Health::Down);
+
+        if let Some((_, tracer)) = &self.tracer {
+            let mut span_attributes = attributes.to_vec();
+            span_attributes.extend([
+                KeyValue::new("http.request.method", state.method.clone()),
+                KeyValue::new("statui.health", health.label().to_lowercase()),
+                KeyValue::new("statui.latency_ms", result.latency.as_millis() as i64),
+            ]);
+            if let CheckStatus::Success { code, .. } = result.status {
+                span_attributes.push(KeyValue::new("http.response.status_code", code as i64));
+            }
+            if let Some(error_class) = result.status.error_class() {
+                span_attributes.push(KeyValue::new("error.type", error_class));
+            }
+
+            // The check ended when its result came in
+            let start = at.checked_sub(result.latency).unwrap_or(at);
+            let mut span = tracer
+                .span_builder("statui.check")
+                .with_kind(SpanKind::Client)
+                .with_start_time(start)
+                .with_attributes(span_attributes)
+                .start(tracer);
+            if health != Health::Up {
+                span.set_status(Status::error(state.health_reason.clone()));
+            }
+            span.end_with_timestamp(at);
+        }
+
+        if let Some((_, instruments)) = &self.meter {
+            let up = (health != Health::Down) as u64;
+            instruments.up.record(up, &attributes);
+            instruments
+                .latency
+                .record(result.latency.as_secs_f64(), &attributes);
+        }
+    }
+}
+
+fn instruments(meter: &Meter) -> Instruments {
+    Instruments {
+        up: meter
+            .u64_gauge("statui.up")
+            .with_description("Whether the endpoint is up (1) or down (0).")
+            .build(),
+        latency: meter
+            .f64_histogram("statui.latency")
+            .with_description("Latency of the checks.")
+            .with_unit("s")
+            .with_boundaries(LATENCY_BUCKETS.to_vec())
+            .build(),
+    }
+}
+
+fn span_exporter(config: &OtlpConfig) -> Result<SpanExporter> {
+    let timeout = Duration::from_secs(config.timeout);
+    let exporter = match config.protocol {
+        OtlpProtocol::Grpc => {
+            let endpoint = grpc_endpoint(config);
+            let mut builder = SpanExporter::builder()
+                .with_tonic()
+                .with_timeout(timeout)
+                .with_metadata(metadata(config)?);
+            if endpoint.starts_with("https") {
+                builder = builder.with_tls_config(ClientTlsConfig::new().with_webpki_roots());
+            }
+            builder.with_endpoint(endpoint).build()?
+        }
+        OtlpProtocol::Http => SpanExporter::builder()
+            .with_http()
+            .with_endpoint(http_endpoint(config, "traces"))
+            .with_timeout(timeout)
+            .with_headers(interpolated_headers(config)?)
+            .build()?,
+    };
+    Ok(exporter)
+}
+
+fn metric_exporter(config: &OtlpConfig) -> Result<MetricExporter> {
+    let timeout = Duration::from_secs(config.timeout);
+    let exporter = match config.protocol {
+        OtlpProtocol::Grpc => {
+            let endpoint = grpc_endpoint(config);
+            let mut builder = MetricExporter::builder()
+                .with_tonic()
+                .with_timeout(timeout)
+                .with_metadata(metadata(config)?);
+            if endpoint.starts_with("https") {
+                builder = builder.with_tls_config(ClientTlsConfig::new().with_webpki_roots());
+            }
+            builder.with_endpoint(endpoint).build()?
+        }
+        OtlpProtocol::Http => MetricExporter::builder()
+            .with_http()
+            .with_endpoint(http_endpoint(config, "metrics"))
+            .with_timeout(timeout)
+            .with_headers(interpolated_headers(config)?)
+            .build()?,
+    };
+    Ok(exporter)
+}
+
+fn grpc_endpoint(config: &OtlpConfig) -> String {
+    config
+        .endpoint
+        .clone()
+        .unwrap_or_else(|| "http://localhost:4317".to_string())
+}
+
+/// Each signal has its own path over HTTP, e.g. /v1/traces.
+fn http_endpoint(config: &OtlpConfig, signal: &str) -> String {
+    let base = config
+        .endpoint
+        .as_deref()
+        .unwrap_or("http://localhost:4318");
+    format!("{}/v1/{signal}", base.trim_end_matches('/'))
+}
+
+/// The headers with their `${VAR}`s filled in from the environment.
+fn interpolated_headers(config: &OtlpConfig) -> Result<HashMap<String, String>> {
+    config
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let value = interpolate_env(value)
+                .map_err(|err| eyre!("Invalid OTLP header value for '{name}': {err}"))?;
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+/// The headers as gRPC metadata.
+fn metadata(config: &OtlpConfig) -> Result<MetadataMap> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in interpolated_headers(config)? {
+        let name = HeaderName::from_bytes(name.to_lowercase().as_bytes())
+            .map_err(|err| eyre!("Invalid OTLP header name '{name}': {err}"))?;
+        let value = HeaderValue::from_str(&value)
+            .map_err(|err| eyre!("Invalid OTLP header value for '{name}': {err}"))?;
+        headers.insert(name, value);
+    }
+    Ok(MetadataMap::from_headers(headers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Endpoint, StatuiConfig};
+    use opentelemetry::Value;
+    use opentelemetry_sdk::{
+        metrics::{
+            InMemoryMetricExporter,
+            data::{AggregatedMetrics, MetricData},
+        },
+        trace::InMemorySpanExporter,
+    };
+
+    fn attribute<'a>(attributes: impl IntoIterator<Item = &'a KeyValue>, key: &str) -> Value {
+        attributes
+            .into_iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.clone())
+            .unwrap_or_else(|| panic!("no {key} attribute"))
+    }
+
+    #[test]
+    fn maps_checks_to_spans_and_instruments() {
+        let spans = InMemorySpanExporter::default();
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_simple_exporter(spans.clone())
+            .build();
+        let tracer = tracer_provider.tracer(SCOPE);
+
+        let metrics = InMemoryMetricExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(metrics.clone()).build())
+            .build();
+        let instruments = instruments(&meter_provider.meter(SCOPE));
+
+        let telemetry = Telemetry {
+            tracer: Some((tracer_provider.clone(), tracer)),
+            meter: Some((meter_provider.clone(), instruments)),
+        };
+
+        let endpoint = Endpoint {
+            name: "api".to_string(),
+            url: "https://api.example.com".to_string(),
+            tags: vec!["prod".to_string()],
+            ..Endpoint::default()
+        };
+        let config = StatuiConfig::default();
+        let mut state = EndpointState::new(&endpoint, &config);
+        let result = CheckResult {
+            name: "api".to_string(),
+            status: CheckStatus::Success {
+                code: 503,
+                text: "Service Unavailable".to_string(),
+            },
+            latency: Duration::from_millis(20),
+            cert_expiry: None,
+            generation: 0,
+        };
+        let at = SystemTime::now();
+        state.record(at, result.clone(), &config);
+        telemetry.record(&state, at, &result);
+
+        let spans = spans.get_finished_spans().unwrap();
+        let span = &spans[0];
+        assert_eq!(span.name, "statui.check");
+        assert_eq!(span.span_kind, SpanKind::Client);
+        assert_eq!(span.end_time, at);
+        assert_eq!(span.start_time, at - Duration::from_millis(20));
+        assert!(matches!(span.status, Status::Error { .. }));
+        assert_eq!(attribute(&span.attributes, "statui.endpoint"), "api".into());
+        assert_eq!(
+            attribute(&span.attributes, "http.request.method"),
+            "GET".into()
+        );
+        assert_eq!(
+            attribute(&span.attributes, "http.response.status_code"),
+            503.into()
+        );
+        assert_eq!(attribute(&span.attributes, "error.type"), "http_5xx".into());
+        assert_eq!(attribute(&span.attributes, "statui.health"), "down".into());
+
+        meter_provider.force_flush().unwrap();
+        let exported = metrics.get_finished_metrics().unwrap();
+        let instruments: Vec<_> = exported
+            .iter()
+            .flat_map(|resource| resource.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .collect();
+
+        let up = instruments
+            .iter()
+            .find(|m| m.name() == "statui.up")
+            .unwrap();
+        let AggregatedMetrics::U64(MetricData::Gauge(up)) = up.data() else {
+            panic!("statui.up isn't a u64 gauge");
+        };
+        let point = up.data_points().next().unwrap();
+        assert_eq!(point.value(), 0);
+        assert_eq!(attribute(point.attributes(), "statui.tags"), "prod".into());
+
+        let latency = instruments
+            .iter()
+            .find(|m| m.name() == "statui.latency")
+            .unwrap();
+        assert_eq!(latency.unit(), "s");
+        let AggregatedMetrics::F64(MetricData::Histogram(latency)) = latency.data() else {
+            panic!("statui.latency isn't an f64 histogram");
+        };
+        let point = latency.data_points().next().unwrap();
+        assert_eq!(point.count(), 1);
+        assert!((point.sum() - 0.02).abs() < 1e-9);
+        assert_eq!(point.bounds().collect::<Vec<_>>(), LATENCY_BUCKETS.to_vec());
+    }
+}
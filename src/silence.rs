@@ -0,0 +1,240 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{APP_NAME, APP_ORGANIZATION, APP_QUALIFIER};
+
+// Acks, snoozes and mutes.
+//
+// They keep the notifications and hooks of an endpoint quiet while someone
+// is already on it. Like the notifications they silence, they're per
+// endpoint: acking covers every alert of the endpoint rather than a single
+// rule. They're saved to a small JSON file in the data directory so they
+// survive restarts, the whole file is rewritten on every change.
+
+const SILENCES_FILE: &str = "silences.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SilenceKind {
+    // Lasts until the endpoint is up with no active alert, whichever
+    // alert or health change it was acked for
+    Ack { note: Option<String> },
+    Snooze { until_ms: u64 },
+    Mute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Silence {
+    #[serde(flatten)]
+    pub kind: SilenceKind,
+    /// Unix timestamp in milliseconds
+    pub since_ms: u64,
+}
+
+impl Silence {
+    pub fn new(kind: SilenceKind, at: SystemTime) -> Self {
+        Silence {
+            kind,
+            since_ms: to_millis(at),
+        }
+    }
+
+    pub fn since(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.since_ms)
+    }
+
+    /// When a snooze runs out, None for acks and mutes.
+    pub fn until(&self) -> Option<SystemTime> {
+        match self.kind {
+            SilenceKind::Snooze { until_ms } => Some(UNIX_EPOCH + Duration::from_millis(until_ms)),
+            _ => None,
+        }
+    }
+
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.until().is_some_and(|until| until <= now)
+    }
+}
+
+/// The silences of every endpoint, at most one per endpoint.
+pub struct SilenceStore {
+    path: Option<PathBuf>,
+    silences: HashMap<String, Silence>,
+}
+
+impl SilenceStore {
+    /// Loads the saved silences, a missing or corrupt file starts empty.
+    pub fn load() -> Self {
+        let path = ProjectDirs::from(APP_QUALIFIER, APP_ORGANIZATION, APP_NAME)
+            .map(|proj_dirs| proj_dirs.data_dir().join(SILENCES_FILE));
+        Self::open(path)
+    }
+
+    /// Loads the silences saved at `path`, without a path nothing is saved.
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let silences = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        SilenceStore { path, silences }
+    }
+
+    pub fn get(&self, endpoint: &str) -> Option<&Silence> {
+        self.silences.get(endpoint)
+    }
+
+    pub fn is_silenced(&self, endpoint: &str, now: SystemTime) -> bool {
+        self.get(endpoint)
+            .is_some_and(|silence| !silence.is_expired(now))
+    }
+
+    pub fn set(&mut self, endpoint: &str, silence: Silence) -> io::Result<()> {
+        self.silences.insert(endpoint.to_string(), silence);
+        self.save()
+    }
+
+    pub fn clear(&mut self, endpoint: &str) -> io::Result<()> {
+        if self.silences.remove(endpoint).is_none() {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    /// Drops the snoozes that ran out and returns their endpoints.
+    pub fn expire(&mut self, now: SystemTime) -> io::Result<Vec<String>> {
+        let expired: Vec<String> = self
+            .silences
+            .iter()
+            .filter(|(_, silence)| silence.is_expired(now))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        if expired.is_empty() {
+            return Ok(expired);
+        }
+
+        for name in &expired {
+            self.silences.remove(name);
+        }
+        self.save()?;
+        Ok(expired)
+    }
+
+    /// Drops the silences of endpoints that no longer exist.
+    pub fn retain(&mut self, endpoints: &[String]) -> io::Result<()> {
+        let before = self.silences.len();
+        self.silences.retain(|name, _| endpoints.contains(name));
+
+        if self.silences.len() == before {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    /// Writes to a temporary file first so a crash can't leave a half
+    /// written file behind.
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.silences)?)?;
+        fs::rename(tmp, path)
+    }
+}
+
+pub fn to_millis(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "statui-silences-{test}-{}.json",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn survives_a_restart() {
+        let path = path("restart");
+        let now = SystemTime::now();
+
+        let mut store = SilenceStore::open(Some(path.clone()));
+        store
+            .set("api", Silence::new(SilenceKind::Mute, now))
+            .unwrap();
+        let note = Some("on it".to_string());
+        store
+            .set("web", Silence::new(SilenceKind::Ack { note }, now))
+            .unwrap();
+        store.clear("web").unwrap();
+
+        let reopened = SilenceStore::open(Some(path.clone()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(reopened.get("api"), store.get("api"));
+        assert!(reopened.is_silenced("api", now));
+        assert!(reopened.get("web").is_none());
+    }
+
+    #[test]
+    fn snoozes_run_out() {
+        let path = path("snooze");
+        let now = SystemTime::now();
+        let until = now + Duration::from_secs(60);
+
+        let mut store = SilenceStore::open(Some(path.clone()));
+        let snooze = SilenceKind::Snooze {
+            until_ms: to_millis(until),
+        };
+        store.set("api", Silence::new(snooze, now)).unwrap();
+        store
+            .set("web", Silence::new(SilenceKind::Mute, now))
+            .unwrap();
+
+        assert!(store.is_silenced("api", now));
+        assert!(!store.is_silenced("api", until));
+        assert!(store.expire(now).unwrap().is_empty());
+        assert_eq!(store.expire(until).unwrap(), ["api"]);
+
+        // Mutes never run out
+        let reopened = SilenceStore::open(Some(path.clone()));
+        let _ = fs::remove_file(&path);
+        assert!(reopened.get("api").is_none());
+        assert!(reopened.is_silenced("web", until + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn forgets_removed_endpoints() {
+        let mut store = SilenceStore::open(None);
+        let now = SystemTime::now();
+        store
+            .set("api", Silence::new(SilenceKind::Mute, now))
+            .unwrap();
+        store
+            .set("web", Silence::new(SilenceKind::Mute, now))
+            .unwrap();
+
+        store.retain(&["web".to_string()]).unwrap();
+        assert!(store.get("api").is_none());
+        assert!(store.get("web").is_some());
+    }
+}
@@ -0,0 +1,665 @@
+use std::{
+    fmt::Write,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpStream, UdpSocket, lookup_host},
+    sync::mpsc::{self, Receiver, Sender},
+    task::JoinHandle,
+    time::{MissedTickBehavior, interval, timeout},
+};
+
+use crate::{
+    backend::{CheckResult, CheckStatus},
+    config::{SinkConfig, SinkKind, SinkTag, SinkTransport, interpolate_env},
+    state::EndpointState,
+    stats::incidents::Health,
+};
+
+// Push sinks for time series databases.
+//
+// The App hands every result it records to the sinks as a point. Each sink
+// runs in its own task, renders the points as InfluxDB line protocol,
+// StatsD or Graphite lines and pushes them in batches. The App never waits
+// on a sink: points are dropped when a sink falls behind, and pushes that
+// failed are reported back to the status line.
+
+const POINT_BUFFER_SIZE: usize = 1024;
+const FAILURE_BUFFER_SIZE: usize = 32;
+
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Lines sent over udp are packed into datagrams that fit a typical MTU
+const MAX_DATAGRAM_SIZE: usize = 1432;
+
+/// A check as it's sent to the sinks.
+pub struct Point {
+    endpoint: String,
+    url: String,
+    method: String,
+    group: Option<String>,
+    tags: Vec<String>,
+
+    at: SystemTime,
+    health: Health,
+    code: Option<u16>,
+    error_class: Option<&'static str>,
+    latency: Duration,
+}
+
+impl Point {
+    /// A result that came in at `at` right after it was recorded in `state`.
+    pub fn new(
+        state: &EndpointState,
+        group: Option<String>,
+        at: SystemTime,
+        result: &CheckResult,
+    ) -> Self {
+        let code = match result.status {
+            CheckStatus::Success { code, .. } => Some(code),
+            CheckStatus::Error { .. } => None,
+        };
+
+        Point {
+            endpoint: state.name.clone(),
+            url: state.url.clone(),
+            method: state.method.clone(),
+            group,
+            tags: state.tags.clone(),
+            at,
+            health: state.health.unwrap_or(Health::Down),
+            code,
+            error_class: result.status.error_class(),
+            latency: result.latency,
+        }
+    }
+
+    fn up(&self) -> u8 {
+        (self.health != Health::Down) as u8
+    }
+
+    /// Rounded to the microsecond so the lines stay short.
+    fn latency_ms(&self) -> f64 {
+        self.latency.as_micros() as f64 / 1000.0
+    }
+}
+
+/// A push that failed or a sink that fell behind, along with the checks
+/// it lost.
+pub struct SinkFailure {
+    pub sink: String,
+    pub error: String,
+    pub dropped: usize,
+}
+
+/// The running sinks, stopped when dropped after pushing what they hold.
+pub struct Sinks {
+    queues: Vec<SinkQueue>,
+    failures: Receiver<SinkFailure>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+/// The points on their way to a sink.
+struct SinkQueue {
+    name: String,
+    tx: Sender<Arc<Point>>,
+    // Points that didn't fit in the queue since the last report
+    dropped: usize,
+}
+
+impl Sinks {
+    /// Starts a task for every sink, returns None if there are none.
+    ///
+    /// Needs to be called from within the tokio runtime.
+    pub fn spawn(configs: &[SinkConfig]) -> Option<Self> {
+        if configs.is_empty() {
+            return None;
+        }
+
+        let (failure_tx, failures) = mpsc::channel(FAILURE_BUFFER_SIZE);
+        let mut tasks = Vec::new();
+        let queues = configs
+            .iter()
+            .map(|config| {
+                let (tx, rx) = mpsc::channel(POINT_BUFFER_SIZE);
+                tasks.push(tokio::spawn(run_sink(
+                    config.clone(),
+                    rx,
+                    failure_tx.clone(),
+                )));
+                SinkQueue {
+                    name: config.name.clone(),
+                    tx,
+                    dropped: 0,
+                }
+            })
+            .collect();
+
+        Some(Sinks {
+            queues,
+            failures,
+            tasks,
+        })
+    }
+
+    /// Closes the queues and waits for every sink to push what it still
+    /// holds.
+    pub async fn close(self) {
+        drop(self.queues);
+        for task in self.tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// Queues the point for every sink, a sink that fell behind misses it.
+    pub fn push(&mut self, point: Point) {
+        let point = Arc::new(point);
+        for queue in &mut self.queues {
+            if queue.tx.try_send(point.clone()).is_err() {
+                queue.dropped += 1;
+            }
+        }
+    }
+
+    /// The failures reported since the last call, and the sinks that
+    /// dropped points since then.
+    pub fn failures(&mut self) -> Vec<SinkFailure> {
+        let mut failures = Vec::new();
+        while let Ok(failure) = self.failures.try_recv() {
+            failures.push(failure);
+        }
+
+        for queue in &mut self.queues {
+            if queue.dropped == 0 {
+                continue;
+            }
+
+            let error = if queue.tx.is_closed() {
+                "it stopped".to_string()
+            } else {
+                "it fell behind".to_string()
+            };
+            failures.push(SinkFailure {
+                sink: queue.name.clone(),
+                error,
+                dropped: std::mem::take(&mut queue.dropped),
+            });
+        }
+        failures
+    }
+}
+
+/// Where a sink pushes its lines to.
+enum Target {
+    Http {
+        client: reqwest::Client,
+        url: String,
+    },
+    Udp {
+        socket: UdpSocket,
+        address: SocketAddr,
+    },
+    Tcp {
+        address: String,
+    },
+}
+
+async fn run_sink(
+    config: SinkConfig,
+    mut rx: Receiver<Arc<Point>>,
+    failure_tx: Sender<SinkFailure>,
+) {
+    let report = |error: String, dropped: usize| {
+        let _ = failure_tx.try_send(SinkFailure {
+            sink: config.name.clone(),
+            error,
+            dropped,
+        });
+    };
+
+    let target = match Target::new(&config).await {
+        Ok(target) => target,
+        Err(error) => {
+            report(error, 0);
+            return;
+        }
+    };
+
+    let batch_size = config.batch_size.max(1);
+    let mut ticker = interval(Duration::from_secs(config.flush_interval.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // The lines of the points in the batch
+    let mut lines: Vec<String> = Vec::new();
+    let mut points = 0;
+    loop {
+        tokio::select! {
+            point = rx.recv() => {
+                let Some(point) = point else {
+                    break;
+                };
+
+                render(&config, &point, &mut lines);
+                points += 1;
+                if lines.len() < batch_size {
+                    continue;
+                }
+            }
+            _ = ticker.tick() => {}
+        }
+
+        if lines.is_empty() {
+            continue;
+        }
+        if let Err(error) = target.push(&config, &lines).await {
+            report(error, points);
+        }
+        lines.clear();
+        points = 0;
+    }
+
+    // The App let go of the sink, push what's left
+    if !lines.is_empty()
+        && let Err(error) = target.push(&config, &lines).await
+    {
+        report(error, points);
+    }
+}
+
+impl Target {
+    async fn new(config: &SinkConfig) -> Result<Self, String> {
+        let transport = config.transport.unwrap_or(match config.kind {
+            SinkKind::Influx => SinkTransport::Http,
+            SinkKind::Statsd => SinkTransport::Udp,
+            SinkKind::Graphite => SinkTransport::Tcp,
+        });
+
+        let supported = match config.kind {
+            SinkKind::Influx => transport != SinkTransport::Tcp,
+            SinkKind::Statsd => transport == SinkTransport::Udp,
+            SinkKind::Graphite => transport != SinkTransport::Http,
+        };
+        if !supported {
+            return Err(format!(
+                "{} sinks can't be sent over {}",
+                kind_label(config.kind),
+                transport_label(transport)
+            ));
+        }
+
+        if transport == SinkTransport::Http {
+            let Some(url) = &config.url else {
+                return Err("http sinks need a url".to_string());
+            };
+            let client = reqwest::Client::builder()
+                .timeout(PUSH_TIMEOUT)
+                .build()
+                .map_err(|err| err.to_string())?;
+            return Ok(Target::Http {
+                client,
+                url: interpolate_env(url)?,
+            });
+        }
+
+        let Some(address) = &config.address else {
+            return Err(format!(
+                "{} sinks need an address",
+                transport_label(transport)
+            ));
+        };
+
+        if transport == SinkTransport::Tcp {
+            return Ok(Target::Tcp {
+                address: address.clone(),
+            });
+        }
+
+        let address = lookup_host(address)
+            .await
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .ok_or_else(|| format!("couldn't resolve {address}"))?;
+        let local = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(local)
+            .await
+            .map_err(|err| format!("couldn't open a udp socket: {err}"))?;
+        Ok(Target::Udp { socket, address })
+    }
+
+    async fn push(&self, config: &SinkConfig, lines: &[String]) -> Result<(), String> {
+        match self {
+            Target::Http { client, url } => {
+                let mut request = client
+                    .post(url)
+                    .header("Content-Type", "text/plain; charset=utf-8")
+                    .body(lines.join("\n"));
+                for (key, value) in &config.headers {
+                    request = request.header(key, interpolate_env(value)?);
+                }
+
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|err| format!("couldn't reach it: {err}"))?;
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(());
+                }
+
+                let body = response.text().await.unwrap_or_default();
+                let body = body.trim();
+                if body.is_empty() {
+                    Err(format!("it replied {status}"))
+                } else {
+                    Err(format!("it replied {status}: {body}"))
+                }
+            }
+            Target::Udp { socket, address } => {
+                for datagram in datagrams(lines) {
+                    socket
+                        .send_to(datagram.as_bytes(), address)
+                        .await
+                        .map_err(|err| format!("couldn't send to {address}: {err}"))?;
+                }
+                Ok(())
+            }
+            Target::Tcp { address } => {
+                let mut payload = lines.join("\n");
+                payload.push('\n');
+
+                let send = async {
+                    let mut stream = TcpStream::connect(address).await?;
+                    stream.write_all(payload.as_bytes()).await?;
+                    stream.shutdown().await
+                };
+                match timeout(PUSH_TIMEOUT, send).await {
+                    Ok(Ok(())) => Ok(()),
+                    Ok(Err(err)) => Err(format!("couldn't send to {address}: {err}")),
+                    Err(_) => Err(format!("{address} timed out")),
+                }
+            }
+        }
+    }
+}
+
+/// Packs the lines into as few datagrams as possible.
+fn datagrams(lines: &[String]) -> Vec<String> {
+    let mut datagrams: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        if !current.is_empty() && current.len() + 1 + line.len() > MAX_DATAGRAM_SIZE {
+            datagrams.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+
+    if !current.is_empty() {
+        datagrams.push(current);
+    }
+    datagrams
+}
+
+fn render(config: &SinkConfig, point: &Point, lines: &mut Vec<String>) {
+    match config.kind {
+        SinkKind::Influx => lines.push(influx_line(config, point)),
+        SinkKind::Statsd => statsd_lines(config, point, lines),
+        SinkKind::Graphite => graphite_lines(config, point, lines),
+    }
+}
+
+/// The metadata of the point the sink sends as tags. Endpoint tags are
+/// joined with commas, StatsD sends them one by one instead.
+fn tags(config: &SinkConfig, point: &Point) -> Vec<(&'static str, String)> {
+    let default = match config.kind {
+        SinkKind::Influx => &[SinkTag::Endpoint, SinkTag::Group, SinkTag::Tags][..],
+        SinkKind::Statsd | SinkKind::Graphite => &[][..],
+    };
+
+    config
+        .tags
+        .as_deref()
+        .unwrap_or(default)
+        .iter()
+        .filter_map(|tag| match tag {
+            SinkTag::Endpoint => Some(("endpoint", point.endpoint.clone())),
+            SinkTag::Url => Some(("url", point.url.clone())),
+            SinkTag::Method => Some(("method", point.method.clone())),
+            SinkTag::Group => point.group.clone().map(|group| ("group", group)),
+            SinkTag::Tags => Some(("tags", point.tags.join(","))),
+        })
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+}
+
+/// e.g. `statui,endpoint=api up=1i,latency_ms=12.3,health="up",status_code=200i 1700000000000000000`
+fn influx_line(config: &SinkConfig, point: &Point) -> String {
+    let mut line = influx_escape(&config.measurement, &[',', ' ']);
+    for (key, value) in tags(config, point) {
+        let _ = write!(line, ",{key}={}", influx_escape(&value, &[',', '=', ' ']));
+    }
+
+    let _ = write!(
+        line,
+        " up={}i,latency_ms={},health=\"{}\"",
+        point.up(),
+        point.latency_ms(),
+        point.health.label().to_lowercase()
+    );
+    if let Some(code) = point.code {
+        let _ = write!(line, ",status_code={code}i");
+    }
+    if let Some(error_class) = point.error_class {
+        let _ = write!(line, ",error=\"{error_class}\"");
+    }
+
+    let nanos = point.at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let _ = write!(line, " {}", nanos.as_nanos());
+    line
+}
+
+fn influx_escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// e.g. `statui.api.latency:12.3|ms|#group:backend,prod`
+fn statsd_lines(config: &SinkConfig, point: &Point, lines: &mut Vec<String>) {
+    let prefix = metric_prefix(config, point);
+
+    let mut suffix = String::new();
+    let tags: Vec<String> = tags(config, point)
+        .into_iter()
+        .flat_map(|(key, value)| match key {
+            "tags" => point.tags.iter().map(|tag| statsd_tag(tag)).collect(),
+            _ => vec![format!("{key}:{}", statsd_tag(&value))],
+        })
+        .collect();
+    if !tags.is_empty() {
+        let _ = write!(suffix, "|#{}", tags.join(","));
+    }
+
+    lines.push(format!("{prefix}.up:{}|g{suffix}", point.up()));
+    lines.push(format!(
+        "{prefix}.health:{}|g{suffix}",
+        health_value(point.health)
+    ));
+    lines.push(format!(
+        "{prefix}.latency:{}|ms{suffix}",
+        point.latency_ms()
+    ));
+    if let Some(code) = point.code {
+        lines.push(format!("{prefix}.status_code:{code}|g{suffix}"));
+    }
+}
+
+fn statsd_tag(value: &str) -> String {
+    value.replace([',', '|', '#', '\n'], "_")
+}
+
+/// e.g. `statui.api.latency_ms;group=backend 12.3 1700000000`
+fn graphite_lines(config: &SinkConfig, point: &Point, lines: &mut Vec<String>) {
+    let prefix = metric_prefix(config, point);
+
+    let mut suffix = String::new();
+    for (key, value) in tags(config, point) {
+        let _ = write!(
+            suffix,
+            ";{key}={}",
+            value.replace([';', ' ', '~', '\n'], "_")
+        );
+    }
+
+    let secs = point
+        .at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut push = |metric: &str, value: String| {
+        lines.push(format!("{prefix}.{metric}{suffix} {value} {secs}"));
+    };
+
+    push("up", point.up().to_string());
+    push("health", health_value(point.health).to_string());
+    push("latency_ms", point.latency_ms().to_string());
+    if let Some(code) = point.code {
+        push("status_code", code.to_string());
+    }
+}
+
+/// The measurement followed by the endpoint, e.g. `statui.api`.
+fn metric_prefix(config: &SinkConfig, point: &Point) -> String {
+    let mut prefix: Vec<String> = config.measurement.split('.').map(metric_name).collect();
+    prefix.push(metric_name(&point.endpoint));
+    prefix.join(".")
+}
+
+/// A metric path segment, anything but letters, digits, - and _ becomes _.
+fn metric_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// 0 when up, 1 when degraded and 2 when down.
+fn health_value(health: Health) -> u8 {
+    match health {
+        Health::Up => 0,
+        Health::Degraded => 1,
+        Health::Down => 2,
+    }
+}
+
+fn kind_label(kind: SinkKind) -> &'static str {
+    match kind {
+        SinkKind::Influx => "InfluxDB",
+        SinkKind::Statsd => "StatsD",
+        SinkKind::Graphite => "Graphite",
+    }
+}
+
+fn transport_label(transport: SinkTransport) -> &'static str {
+    match transport {
+        SinkTransport::Http => "http",
+        SinkTransport::Udp => "udp",
+        SinkTransport::Tcp => "tcp",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> SinkConfig {
+        toml::from_str(&format!("name = \"influx\"\nkind = \"influx\"\n{toml}")).unwrap()
+    }
+
+    fn point() -> Point {
+        Point {
+            endpoint: "api, eu".to_string(),
+            url: "https://api.example.com".to_string(),
+            method: "GET".to_string(),
+            group: Some("backend".to_string()),
+            tags: vec!["prod".to_string(), "k=v".to_string()],
+            at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            health: Health::Up,
+            code: Some(200),
+            error_class: None,
+            latency: Duration::from_micros(12_345),
+        }
+    }
+
+    #[tokio::test]
+    async fn closing_pushes_the_last_batch() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        // Nothing would be pushed for an hour without closing
+        let config = config(&format!(
+            "transport = \"udp\"\naddress = \"{address}\"\nbatch_size = 100\nflush_interval = 3600"
+        ));
+
+        let mut sinks = Sinks::spawn(&[config]).unwrap();
+        sinks.push(point());
+        sinks.close().await;
+
+        let mut buf = [0; 1024];
+        let len = timeout(Duration::from_secs(5), socket.recv(&mut buf))
+            .await
+            .expect("the point was pushed")
+            .unwrap();
+        let sent = String::from_utf8_lossy(&buf[..len]);
+        assert!(sent.starts_with("statui,endpoint=api\\,\\ eu"), "{sent}");
+    }
+
+    #[test]
+    fn renders_influx_lines() {
+        let line = influx_line(&config(""), &point());
+        assert_eq!(
+            line,
+            "statui,endpoint=api\\,\\ eu,group=backend,tags=prod\\,k\\=v \
+             up=1i,latency_ms=12.345,health=\"up\",status_code=200i 1700000000000000000"
+        );
+    }
+
+    #[test]
+    fn renders_failed_checks_and_picked_tags() {
+        let point = Point {
+            health: Health::Down,
+            code: None,
+            error_class: Some("timeout"),
+            group: None,
+            ..point()
+        };
+
+        let config = config("measurement = \"up time\"\ntags = [\"method\", \"group\"]");
+        assert_eq!(
+            influx_line(&config, &point),
+            "up\\ time,method=GET up=0i,latency_ms=12.345,health=\"down\",error=\"timeout\" \
+             1700000000000000000"
+        );
+    }
+}
@@ -1,17 +1,44 @@
 use std::{
     collections::{HashMap, VecDeque},
-    time::{Duration, SystemTime},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
 
 use ratatui::widgets::TableState;
+use tokio::sync::mpsc::{self, Receiver, Sender, error::TrySendError};
 
 use crate::{
+    alerts::{Alert, AlertEngine},
+    api::Api,
     backend::{CheckResult, CheckStatus},
-    config::Endpoint,
+    config::{
+        AnomalyConfig, ConfigDiff, ConfigPaths, Endpoint, LatencyThresholds, SloConfig,
+        StatuiConfig,
+    },
+    export::{self, ExportOutcome},
+    form::{EndpointForm, SilencePrompt},
+    history::{self, CheckRecord, HistoryWriter},
+    metrics::Metrics,
+    notify::{
+        Notification, Transition,
+        hook::{HookCommand, HookOutcome},
+    },
+    otel::Telemetry,
+    silence::{Silence, SilenceKind, SilenceStore},
+    sinks::{Point, Sinks},
+    stats::{
+        anomaly::LatencyBaseline,
+        flapping::FlapDetector,
+        incidents::{self, Health, Incident, IncidentTracker},
+        latency::LatencyTracker,
+        slo::SloStatus,
+        uptime::AvailabilityStats,
+    },
 };
 
 const MAX_LATENCY_HISTORY: usize = 100;
 const MAX_LOG_LENGTH: usize = 50;
+const EXPORT_BUFFER_SIZE: usize = 4;
 
 #[derive(PartialEq, Eq)]
 pub enum AppMode {
@@ -19,99 +46,560 @@ pub enum AppMode {
     Inspecting,
 }
 
+/// The main views of the app, only one of them is shown at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Endpoints,
+    Incidents,
+    Alerts,
+}
+
 pub struct App {
     // TODO: might have to refactor into a separate AppState
     // struct if it gets too big
+    pub config: StatuiConfig,
     pub endpoint_order: Vec<String>,
     pub endpoint_states: HashMap<String, EndpointState>,
     pub table_state: TableState,
     pub mode: AppMode,
+    pub view: View,
+    pub incidents_table_state: TableState,
+    pub alerts_table_state: TableState,
+    pub status_message: Option<StatusMessage>,
+
+    pub config_paths: ConfigPaths,
+    // Bumped on every config reload, see `BackendCommand::ApplyDiff`
+    pub config_generation: u64,
+    // A reload the backend was too busy to take, retried on the next tick
+    pub pending_reload: Option<Box<StatuiConfig>>,
+    pub form: Option<EndpointForm>,
+    pub pending_delete: Option<String>,
+    pub prompt: Option<SilencePrompt>,
+
+    pub alerts: AlertEngine,
+    pub silences: SilenceStore,
+    history: Option<HistoryWriter>,
+    metrics: Option<Arc<Metrics>>,
+    api: Option<Arc<Api>>,
+    telemetry: Option<Telemetry>,
+    sinks: Option<Sinks>,
+    // None once the app shut down, see `shutdown`
+    notify_tx: Option<Sender<Notification>>,
+    export_tx: Sender<ExportOutcome>,
+    export_rx: Receiver<ExportOutcome>,
 }
 
 impl App {
-    pub fn new(endpoints: &Vec<Endpoint>) -> Self {
+    pub fn new(
+        config: &StatuiConfig,
+        config_paths: ConfigPaths,
+        notify_tx: Sender<Notification>,
+    ) -> Self {
         let mut endpoint_order = Vec::new();
         let mut endpoint_states = HashMap::new();
         let mut table_state = TableState::default();
 
         table_state.select(Some(0));
 
-        for endpoint in endpoints {
-            let endpoint_state = EndpointState {
-                name: endpoint.name.clone(),
-                url: endpoint.url.clone(),
-
-                // TODO: refactor method handling to have a global default
-                // This will require changing backend.rs and config.rs
-                method: endpoint.method.clone().unwrap_or("GET".to_string()),
-
-                latest_status: None,
-                latest_latency: None,
-                latency_history: VecDeque::new(),
+        for endpoint in &config.endpoints {
+            endpoint_order.push(endpoint.name.clone());
+            endpoint_states.insert(endpoint.name.clone(), EndpointState::new(endpoint, config));
+        }
 
-                latency_stats: LatencyStats {
-                    avg: None,
-                    min: None,
-                    max: None,
-                },
+        let mut status_message = None;
+        let history = match HistoryWriter::spawn(&config.history) {
+            Ok(history) => history,
+            Err(err) => {
+                status_message = Some(StatusMessage {
+                    level: StatusLevel::Error,
+                    text: format!("History disabled: {err}"),
+                    created_at: Instant::now(),
+                });
+                None
+            }
+        };
 
-                availability_stats: AvailabilityStats {
-                    uptime_percent: None,
-                    number_of_checks: 0,
-                    number_of_fails: 0,
-                },
+        if config.history.enabled {
+            load_history(config, &mut endpoint_states);
+        }
 
-                recent_checks: VecDeque::new(),
-            };
+        let telemetry = Telemetry::new(&config.otlp).unwrap_or_else(|err| {
+            status_message = Some(StatusMessage {
+                level: StatusLevel::Error,
+                text: format!("OpenTelemetry export disabled: {err}"),
+                created_at: Instant::now(),
+            });
+            None
+        });
 
-            endpoint_order.push(endpoint.name.clone());
-            endpoint_states.insert(endpoint.name.clone(), endpoint_state);
-        }
+        let (export_tx, export_rx) = mpsc::channel(EXPORT_BUFFER_SIZE);
 
-        Self {
+        let app = Self {
+            config: config.clone(),
             endpoint_order,
             endpoint_states,
             table_state,
             mode: AppMode::Normal,
+            view: View::Endpoints,
+            incidents_table_state: TableState::default().with_selected(Some(0)),
+            alerts_table_state: TableState::default().with_selected(Some(0)),
+            status_message,
+            config_paths,
+            config_generation: 0,
+            pending_reload: None,
+            form: None,
+            pending_delete: None,
+            prompt: None,
+            alerts: AlertEngine::new(),
+            silences: SilenceStore::load(),
+            history,
+            metrics: config.metrics.enabled.then(Metrics::new),
+            api: config.api.enabled.then(Api::new),
+            telemetry,
+            sinks: Sinks::spawn(&config.sinks),
+            notify_tx: Some(notify_tx),
+            export_tx,
+            export_rx,
+        };
+        app.refresh_api();
+        app
+    }
+
+    /// Swaps the running config with a reloaded one and returns the diff
+    /// so the backend can update its workers.
+    ///
+    /// Unchanged endpoints keep their state (history, stats and logs),
+    /// changed endpoints start over (but stay paused if they were) and
+    /// removed ones are dropped.
+    pub fn apply_config(&mut self, config: StatuiConfig) -> ConfigDiff {
+        let diff = ConfigDiff::between(&self.config, &config);
+        self.config_generation += 1;
+
+        for name in &diff.removed {
+            self.endpoint_states.remove(name);
+        }
+
+        for endpoint in diff.added.iter().chain(&diff.changed) {
+            let mut state = EndpointState::new(endpoint, &config);
+            state.generation = self.config_generation;
+            // A changed endpoint that was paused stays paused
+            if let Some(old) = self.endpoint_states.get(&endpoint.name) {
+                state.paused = old.paused;
+            }
+            self.endpoint_states.insert(endpoint.name.clone(), state);
+        }
+
+        // Thresholds, tags, SLOs and global or group settings can change
+        // without the worker restarting
+        for endpoint in &config.endpoints {
+            if let Some(state) = self.endpoint_states.get_mut(&endpoint.name) {
+                state.apply_settings(endpoint, &config);
+            }
+        }
+
+        self.endpoint_order = config.endpoints.iter().map(|e| e.name.clone()).collect();
+        self.alerts
+            .retain(&self.config.alerts, &config.alerts, &self.endpoint_order);
+        if let Some(metrics) = &self.metrics {
+            metrics.retain(&self.endpoint_order);
+        }
+        if let Err(err) = self.silences.retain(&self.endpoint_order) {
+            self.set_status(StatusLevel::Error, format!("Couldn't save silences: {err}"));
+        }
+        // The old sinks push what they hold before they stop
+        if config.sinks != self.config.sinks {
+            self.sinks = Sinks::spawn(&config.sinks);
         }
+        self.config = config;
+        self.refresh_api();
+
+        // Keep the selection within bounds in case endpoints were removed
+        if self.endpoint_order.is_empty() {
+            self.table_state.select(None);
+        } else {
+            let last = self.endpoint_order.len() - 1;
+            let selected = self.table_state.selected().unwrap_or(0).min(last);
+            self.table_state.select(Some(selected));
+        }
+
+        diff
+    }
+
+    pub fn set_status(&mut self, level: StatusLevel, text: impl Into<String>) {
+        self.status_message = Some(StatusMessage {
+            level,
+            text: text.into(),
+            created_at: Instant::now(),
+        });
     }
 
     /// Called when a new CheckResult is received from the backend to update the state.
-    pub fn on_result(&mut self, result: CheckResult) {
+    ///
+    /// Returns false if the result was dropped.
+    pub fn on_result(&mut self, result: CheckResult) -> bool {
         let Some(state) = self.endpoint_states.get_mut(&result.name) else {
+            return false;
+        };
+
+        // A check that was already in flight when the endpoint got paused
+        // shouldn't count towards its stats, neither should the ones from
+        // a worker that was replaced since.
+        if state.paused || result.generation != state.generation {
+            return false;
+        }
+
+        let now = SystemTime::now();
+
+        if let Some(history) = &mut self.history {
+            history.append(CheckRecord::new(now, &result));
+        }
+
+        let name = result.name.clone();
+        state.record(now, result, &self.config);
+        let transition = state.next_transition(now);
+
+        let state = &self.endpoint_states[&name];
+        let slo = slo_status(&self.endpoint_states, state, now);
+        self.alerts
+            .evaluate(now, &self.config.alerts, state, slo.as_ref());
+
+        let group = self
+            .config
+            .endpoints
+            .iter()
+            .find(|endpoint| endpoint.name == state.name)
+            .and_then(|endpoint| endpoint.group.clone());
+
+        if let Some((_, result, _)) = state.recent_checks.front() {
+            if let Some(metrics) = &self.metrics {
+                metrics.record(state, result);
+            }
+            if let Some(telemetry) = &self.telemetry {
+                telemetry.record(state, now, result);
+            }
+            if let Some(sinks) = &mut self.sinks {
+                sinks.push(Point::new(state, group.clone(), now, result));
+            }
+        }
+        if let Some(api) = &self.api {
+            api.update(state, group.as_deref(), now);
+            api.publish(state);
+        }
+
+        // An ack covers the whole endpoint, it lasts until the endpoint is
+        // healthy again and none of its alerts are pending or firing
+        let recovered = state.health == Some(Health::Up) && !self.alerts.has_active(&state.name);
+        let acked = self
+            .silences
+            .get(&state.name)
+            .is_some_and(|silence| matches!(silence.kind, SilenceKind::Ack { .. }));
+        if recovered
+            && acked
+            && let Err(err) = self.silences.clear(&state.name)
+        {
+            self.status_message = Some(StatusMessage {
+                level: StatusLevel::Error,
+                text: format!("Couldn't save silences: {err}"),
+                created_at: Instant::now(),
+            });
+        }
+
+        if let Some(transition) = transition {
+            self.notify(transition);
+        }
+        true
+    }
+
+    /// Hands a transition to the notifier for every configured channel
+    /// and the endpoint's hook.
+    ///
+    /// Acked, snoozed and muted endpoints neither notify nor run their hooks.
+    fn notify(&mut self, transition: Transition) {
+        if self
+            .silences
+            .is_silenced(&transition.endpoint, transition.at)
+        {
+            return;
+        }
+
+        let hook = self
+            .config
+            .endpoints
+            .iter()
+            .find(|endpoint| endpoint.name == transition.endpoint)
+            .and_then(|endpoint| self.config.hook(endpoint, transition.to))
+            .map(|command| HookCommand {
+                command,
+                timeout: Duration::from_secs(self.config.hooks.timeout),
+                max_concurrent: self.config.hooks.max_concurrent,
+            });
+
+        let channels = self.config.notifications.clone();
+        if channels.is_empty() && hook.is_none() {
+            return;
+        }
+
+        let Some(notify_tx) = &self.notify_tx else {
             return;
         };
+        let endpoint = transition.endpoint.clone();
+        let notification = Notification {
+            transition,
+            channels,
+            hook,
+        };
+        // The UI can't wait on the notifier, a transition that doesn't fit
+        // is dropped but at least it doesn't go unnoticed
+        match notify_tx.try_send(notification) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => self.set_status(
+                StatusLevel::Error,
+                format!("Dropped the notification for {endpoint}, the notifier is falling behind"),
+            ),
+            Err(TrySendError::Closed(_)) => self.set_status(
+                StatusLevel::Error,
+                format!("Dropped the notification for {endpoint}, the notifier stopped"),
+            ),
+        }
+    }
+
+    /// Stops sending notifications, history records and points so the
+    /// notifier can finish up. Returns the history writer to close once it's
+    /// safe to block and the sinks to close.
+    pub fn shutdown(&mut self) -> (Option<HistoryWriter>, Option<Sinks>) {
+        self.notify_tx = None;
+        (self.history.take(), self.sinks.take())
+    }
+
+    /// Adds a finished hook run to the log of its endpoint.
+    pub fn on_hook_finished(&mut self, outcome: HookOutcome) {
+        if !outcome.is_success() {
+            let message = format!("Hook for '{}': {}", outcome.endpoint, outcome.describe());
+            self.set_status(StatusLevel::Error, message);
+        }
+
+        if let Some(state) = self.endpoint_states.get_mut(&outcome.endpoint) {
+            state.hook_runs.push_front(outcome);
+            if state.hook_runs.len() > MAX_LOG_LENGTH {
+                state.hook_runs.pop_back();
+            }
+        }
+    }
+
+    /// Silences the endpoint's notifications.
+    pub fn silence(&mut self, endpoint: &str, kind: SilenceKind) {
         let now = SystemTime::now();
+        let message = match &kind {
+            SilenceKind::Ack { .. } => format!("Acknowledged '{endpoint}'"),
+            SilenceKind::Snooze { .. } => format!("Snoozed '{endpoint}'"),
+            SilenceKind::Mute => format!("Muted '{endpoint}'"),
+        };
 
-        // Update latest status and latency
-        state.latest_status = Some(result.status.clone());
-        state.latest_latency = Some(result.latency);
+        match self.silences.set(endpoint, Silence::new(kind, now)) {
+            Ok(()) => self.set_status(StatusLevel::Info, message),
+            Err(err) => {
+                self.set_status(StatusLevel::Error, format!("Couldn't save silences: {err}"))
+            }
+        }
+    }
 
-        // Update history and make sure it's within the allowed length
-        state
-            .latency_history
-            .push_back(result.latency.as_millis() as u64);
+    /// Lifts the ack, snooze or mute of the endpoint.
+    pub fn unsilence(&mut self, endpoint: &str) {
+        match self.silences.clear(endpoint) {
+            Ok(()) => self.set_status(StatusLevel::Info, format!("Unsilenced '{endpoint}'")),
+            Err(err) => {
+                self.set_status(StatusLevel::Error, format!("Couldn't save silences: {err}"))
+            }
+        }
+    }
+
+    /// True if there's something wrong with the endpoint worth acking.
+    pub fn needs_attention(&self, endpoint: &str) -> bool {
+        let unhealthy = self
+            .endpoint_states
+            .get(endpoint)
+            .and_then(|state| state.health)
+            .is_some_and(|health| health != Health::Up);
+
+        unhealthy || self.alerts.has_active(endpoint)
+    }
 
-        if state.latency_history.len() > MAX_LATENCY_HISTORY {
-            state.latency_history.pop_front();
+    /// Lifts the snoozes that ran out.
+    pub fn expire_silences(&mut self) {
+        match self.silences.expire(SystemTime::now()) {
+            Ok(expired) if expired.is_empty() => {}
+            Ok(expired) => {
+                let message = format!("Snooze of '{}' ended", expired.join("', '"));
+                self.set_status(StatusLevel::Info, message);
+            }
+            Err(err) => {
+                self.set_status(StatusLevel::Error, format!("Couldn't save silences: {err}"))
+            }
+        }
+    }
+
+    /// Exports the endpoint's history in the background, see `export::spawn_export`.
+    pub fn export(&mut self, endpoint: &str) {
+        let Some(state) = self.endpoint_states.get(endpoint) else {
+            return;
         };
 
-        // Update latency stats
-        state.latency_stats = calc_latency_stats(&state.latency_history);
+        export::spawn_export(&self.config, state, self.export_tx.clone());
+        self.set_status(StatusLevel::Info, format!("Exporting '{endpoint}'..."));
+    }
 
-        // Update availability stats
-        let is_success = matches!(result.status, CheckStatus::Success { .. });
-        state.availability_stats.update(is_success);
+    /// Shows how the exports that finished since the last call went.
+    pub fn report_exports(&mut self) {
+        while let Ok(outcome) = self.export_rx.try_recv() {
+            match outcome.result {
+                Ok((path, count)) => {
+                    let message = format!(
+                        "Exported {count} checks of '{}' to {}",
+                        outcome.endpoint,
+                        path.display()
+                    );
+                    self.set_status(StatusLevel::Info, message);
+                }
+                Err(err) => self.set_status(StatusLevel::Error, format!("Couldn't export: {err}")),
+            }
+        }
+    }
 
-        // Update recent checks (push to the front and pop from the back
-        // so the recent logs are on top)
-        state.recent_checks.push_front((now, result));
-        if state.recent_checks.len() > MAX_LOG_LENGTH {
-            state.recent_checks.pop_back();
+    /// Shows the pushes to the sinks that failed since the last call.
+    pub fn report_sink_failures(&mut self) {
+        let Some(sinks) = &mut self.sinks else {
+            return;
+        };
+
+        for failure in sinks.failures() {
+            let message = if failure.dropped > 0 {
+                format!(
+                    "Sink '{}' dropped {} checks: {}",
+                    failure.sink, failure.dropped, failure.error
+                )
+            } else {
+                format!("Sink '{}' failed: {}", failure.sink, failure.error)
+            };
+            self.set_status(StatusLevel::Error, message);
+        }
+    }
+
+    /// Shows how many checks didn't make it into the history since the last call.
+    pub fn report_history_drops(&mut self) {
+        let Some(history) = &mut self.history else {
+            return;
+        };
+
+        let dropped = history.take_dropped();
+        if dropped > 0 {
+            let message =
+                format!("Dropped {dropped} checks from the history, the disk can't keep up");
+            self.set_status(StatusLevel::Error, message);
+        }
+    }
+
+    /// Every incident across all endpoints, open ones first
+    /// then the most recent ones.
+    pub fn incidents(&self) -> Vec<&Incident> {
+        let mut incidents: Vec<&Incident> = self
+            .endpoint_order
+            .iter()
+            .filter_map(|name| self.endpoint_states.get(name))
+            .flat_map(|state| state.incidents.all())
+            .collect();
+
+        incidents.sort_by(|a, b| {
+            b.is_open()
+                .cmp(&a.is_open())
+                .then_with(|| b.start.cmp(&a.start))
+        });
+        incidents
+    }
+
+    /// The state served by the JSON API, if it's enabled.
+    pub fn api(&self) -> Option<Arc<Api>> {
+        self.api.clone()
+    }
+
+    /// Hands every endpoint to the JSON API, for changes that didn't come
+    /// from a check like config reloads and pauses.
+    fn refresh_api(&self) {
+        let Some(api) = &self.api else {
+            return;
         };
+
+        let now = SystemTime::now();
+        api.set_config(&self.config);
+        api.retain(&self.endpoint_order);
+        for endpoint in &self.config.endpoints {
+            if let Some(state) = self.endpoint_states.get(&endpoint.name) {
+                api.update(state, endpoint.group.as_deref(), now);
+            }
+        }
+    }
+
+    /// The metrics served by the Prometheus exporter, if it's enabled.
+    pub fn metrics(&self) -> Option<Arc<Metrics>> {
+        self.metrics.clone()
+    }
+
+    /// Every alert, firing ones first.
+    pub fn alerts(&self) -> Vec<&Alert> {
+        self.alerts.all()
+    }
+
+    pub fn show_view(&mut self, view: View) {
+        self.view = view;
+    }
+
+    /// The endpoint of the row highlighted in the current view.
+    pub fn highlighted_endpoint(&self) -> Option<String> {
+        match self.view {
+            View::Endpoints => self.selected_endpoint_name().cloned(),
+            View::Incidents => self
+                .incidents_table_state
+                .selected()
+                .and_then(|i| self.incidents().get(i).map(|i| i.endpoint.clone())),
+            View::Alerts => self
+                .alerts_table_state
+                .selected()
+                .and_then(|i| self.alerts().get(i).map(|a| a.endpoint.clone())),
+        }
+    }
+
+    /// Selects the endpoint of the incident or alert highlighted in the
+    /// current view and opens it in the inspector.
+    pub fn jump_to_selected_endpoint(&mut self) {
+        if self.view == View::Endpoints {
+            return;
+        }
+        let Some(name) = self.highlighted_endpoint() else {
+            return;
+        };
+        let Some(index) = self.endpoint_order.iter().position(|n| *n == name) else {
+            return;
+        };
+
+        self.table_state.select(Some(index));
+        self.view = View::Endpoints;
+        self.mode = AppMode::Inspecting;
     }
 
     pub fn next_row(&mut self) {
+        match self.view {
+            View::Incidents => {
+                let len = self.incidents().len();
+                select_next(&mut self.incidents_table_state, len);
+                return;
+            }
+            View::Alerts => {
+                let len = self.alerts().len();
+                select_next(&mut self.alerts_table_state, len);
+                return;
+            }
+            View::Endpoints => {}
+        }
+
         if self.endpoint_order.is_empty() {
             return;
         }
@@ -130,6 +618,20 @@ impl App {
     }
 
     pub fn previous_row(&mut self) {
+        match self.view {
+            View::Incidents => {
+                let len = self.incidents().len();
+                select_previous(&mut self.incidents_table_state, len);
+                return;
+            }
+            View::Alerts => {
+                let len = self.alerts().len();
+                select_previous(&mut self.alerts_table_state, len);
+                return;
+            }
+            View::Endpoints => {}
+        }
+
         if self.endpoint_order.is_empty() {
             return;
         }
@@ -147,6 +649,43 @@ impl App {
         self.table_state.select(Some(i));
     }
 
+    /// Returns the name of the endpoint currently selected in the table.
+    pub fn selected_endpoint_name(&self) -> Option<&String> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.endpoint_order.get(i))
+    }
+
+    /// Returns the config of the endpoint currently selected in the table.
+    pub fn selected_endpoint_config(&self) -> Option<&Endpoint> {
+        let name = self.selected_endpoint_name()?;
+        self.config.endpoints.iter().find(|e| &e.name == name)
+    }
+
+    /// Where `state` stands against its SLO, see `slo_status`.
+    pub fn slo_status(&self, state: &EndpointState, now: SystemTime) -> Option<SloStatus> {
+        slo_status(&self.endpoint_states, state, now)
+    }
+
+    /// Returns true if every endpoint is paused.
+    pub fn all_paused(&self) -> bool {
+        self.endpoint_states.values().all(|state| state.paused)
+    }
+
+    pub fn set_paused(&mut self, name: &str, paused: bool) {
+        if let Some(state) = self.endpoint_states.get_mut(name) {
+            state.set_paused(paused);
+        }
+        self.refresh_api();
+    }
+
+    pub fn set_all_paused(&mut self, paused: bool) {
+        for state in self.endpoint_states.values_mut() {
+            state.set_paused(paused);
+        }
+        self.refresh_api();
+    }
+
     pub fn toggle_inspect(&mut self) {
         match self.mode {
             AppMode::Normal => self.mode = AppMode::Inspecting,
@@ -155,6 +694,138 @@ impl App {
     }
 }
 
+/// Where `state` stands against its SLO, if it has one.
+///
+/// A group's SLO is measured over every endpoint sharing it, so they all
+/// report the same error budget.
+fn slo_status(
+    endpoint_states: &HashMap<String, EndpointState>,
+    state: &EndpointState,
+    now: SystemTime,
+) -> Option<SloStatus> {
+    let slo = state.slo?;
+    let stats: Vec<&AvailabilityStats> = match &state.slo_group {
+        Some(group) => endpoint_states
+            .values()
+            .filter(|other| other.slo_group.as_ref() == Some(group))
+            .map(|other| &other.availability_stats)
+            .collect(),
+        None => vec![&state.availability_stats],
+    };
+    SloStatus::compute(slo, &stats, now)
+}
+
+fn select_next(table_state: &mut TableState, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    // Wrap around to top
+    let i = table_state.selected().map_or(0, |i| (i + 1) % len);
+    table_state.select(Some(i));
+}
+
+fn select_previous(table_state: &mut TableState, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    // Wrap around to bottom
+    let i = table_state.selected().map_or(0, |i| (i + len - 1) % len);
+    table_state.select(Some(i));
+}
+
+/// Replays the persisted history into the endpoint states.
+///
+/// Gaps longer than a couple of check intervals (statui wasn't running or
+/// the endpoint was paused) don't count towards uptime either way.
+fn load_history(config: &StatuiConfig, endpoint_states: &mut HashMap<String, EndpointState>) {
+    let now = SystemTime::now();
+    let load_period = Duration::from_secs(config.history.load_days * 24 * 60 * 60);
+    let since = now
+        .checked_sub(load_period)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut last_seen: HashMap<String, SystemTime> = HashMap::new();
+
+    for record in history::load_since(&config.history, since) {
+        let Some(state) = endpoint_states.get_mut(&record.name) else {
+            continue;
+        };
+        let Some(endpoint) = config.endpoints.iter().find(|e| e.name == record.name) else {
+            continue;
+        };
+
+        let interval = endpoint.interval.unwrap_or(config.default_interval);
+        let timeout = endpoint.timeout.unwrap_or(config.default_timeout);
+        let max_gap = Duration::from_secs(2 * interval + timeout);
+
+        let at = record.time();
+        if let Some(previous) = last_seen.get(&record.name)
+            && at.duration_since(*previous).unwrap_or_default() > max_gap
+        {
+            state.availability_stats.pause(*previous);
+        }
+
+        state.replay(at, record.to_result(), config);
+        last_seen.insert(record.name.clone(), at);
+    }
+
+    for state in endpoint_states.values_mut() {
+        state.latency.refresh();
+    }
+
+    // statui wasn't running between the last stored check and now, the
+    // health it had then was already notified (or it was flapping)
+    for (name, last) in last_seen {
+        if let Some(state) = endpoint_states.get_mut(&name) {
+            state.availability_stats.pause(last);
+            state.notified_health = state.health.zip(state.health_since);
+        }
+    }
+}
+
+/// Works out the health of a check and why, a critically slow response or
+/// a latency anomaly can degrade an otherwise healthy check.
+pub fn assess_health(
+    at: SystemTime,
+    result: &CheckResult,
+    thresholds: LatencyThresholds,
+    baseline: &mut LatencyBaseline,
+    config: &AnomalyConfig,
+) -> (Health, String) {
+    let mut health = Health::of(result);
+    let mut reason = incidents::describe(&result.status);
+
+    if matches!(result.status, CheckStatus::Success { .. }) {
+        let latency_ms = result.latency.as_millis() as u64;
+        let anomalous = baseline.update(at, latency_ms, config);
+
+        if health == Health::Up && thresholds.is_critical(latency_ms) {
+            health = Health::Degraded;
+            reason = format!("Slow response ({}ms)", latency_ms);
+        } else if anomalous && config.degrade_health && health == Health::Up {
+            health = Health::Degraded;
+            reason = format!("Latency anomaly ({}ms)", latency_ms);
+        }
+    }
+
+    (health, reason)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusLevel {
+    Info,
+    Error,
+}
+
+/// A message shown in the status line of the footer.
+pub struct StatusMessage {
+    pub level: StatusLevel,
+    pub text: String,
+    pub created_at: Instant,
+}
+
 /// The state of an Endpoint
 ///
 /// Keeps track of the latest status, latency and latency history.
@@ -162,58 +833,335 @@ pub struct EndpointState {
     pub name: String,
     pub url: String,
     pub method: String,
+    pub thresholds: LatencyThresholds,
+    pub slo: Option<SloConfig>,
+    // The group whose SLO this endpoint shares, if it's not its own
+    pub slo_group: Option<String>,
+    pub tags: Vec<String>,
 
     pub latest_status: Option<CheckStatus>,
     pub latest_latency: Option<Duration>,
     pub latency_history: VecDeque<u64>,
 
-    pub latency_stats: LatencyStats,
+    pub latency: LatencyTracker,
     pub availability_stats: AvailabilityStats,
+    pub incidents: IncidentTracker,
+    pub flap: FlapDetector,
+    pub baseline: LatencyBaseline,
 
-    pub recent_checks: VecDeque<(SystemTime, CheckResult)>,
-}
+    // Health of the latest check, including latency anomalies
+    pub health: Option<Health>,
+    pub health_since: Option<SystemTime>,
+    // The health notifications last reported and since when,
+    // transitions are measured against it rather than the previous check
+    pub notified_health: Option<(Health, SystemTime)>,
+    pub health_reason: String,
+    pub consecutive_failures: u64,
+
+    // Expiry of the TLS certificate seen by the latest https check
+    pub cert_expiry: Option<SystemTime>,
 
-pub struct LatencyStats {
-    pub avg: Option<u64>,
-    pub min: Option<u64>,
-    pub max: Option<u64>,
+    // Newest first, with the health each check was assessed as
+    pub recent_checks: VecDeque<(SystemTime, CheckResult, Health)>,
+    pub hook_runs: VecDeque<HookOutcome>,
+
+    pub paused: bool,
+    // The generation of the worker the results should come from
+    pub generation: u64,
 }
 
-fn calc_latency_stats(latency_history: &VecDeque<u64>) -> LatencyStats {
-    if latency_history.is_empty() {
-        return LatencyStats {
-            avg: None,
-            min: None,
-            max: None,
+impl EndpointState {
+    pub fn new(endpoint: &Endpoint, config: &StatuiConfig) -> Self {
+        EndpointState {
+            name: endpoint.name.clone(),
+            url: endpoint.url.clone(),
+
+            // TODO: refactor method handling to have a global default
+            // This will require changing backend.rs and config.rs
+            method: endpoint.method.clone().unwrap_or("GET".to_string()),
+            thresholds: config.latency_thresholds(endpoint),
+            slo: config.slo(endpoint),
+            slo_group: config.slo_group(endpoint).map(String::from),
+            tags: endpoint.tags.clone(),
+
+            latest_status: None,
+            latest_latency: None,
+            latency_history: VecDeque::new(),
+
+            latency: LatencyTracker::new(),
+
+            availability_stats: AvailabilityStats::new(),
+            incidents: IncidentTracker::new(),
+            flap: FlapDetector::new(),
+            baseline: LatencyBaseline::new(),
+            health: None,
+            health_since: None,
+            notified_health: None,
+            health_reason: String::new(),
+            consecutive_failures: 0,
+            cert_expiry: None,
+
+            recent_checks: VecDeque::new(),
+            hook_runs: VecDeque::new(),
+
+            paused: false,
+            generation: 0,
+        }
+    }
+
+    /// Updates the settings that don't need the worker to restart,
+    /// including the ones resolved from the global and group config.
+    pub fn apply_settings(&mut self, endpoint: &Endpoint, config: &StatuiConfig) {
+        self.thresholds = config.latency_thresholds(endpoint);
+        self.slo = config.slo(endpoint);
+        self.slo_group = config.slo_group(endpoint).map(String::from);
+        self.tags = endpoint.tags.clone();
+    }
+
+    /// Updates the state with a check result that came in at 'at'.
+    pub fn record(&mut self, at: SystemTime, result: CheckResult, config: &StatuiConfig) {
+        self.replay(at, result, config);
+        self.latency.refresh();
+    }
+
+    /// Records a check without refreshing the latency stats, for loading
+    /// the history. Call `latency.refresh` once every check is in.
+    pub fn replay(&mut self, at: SystemTime, result: CheckResult, config: &StatuiConfig) {
+        // Update latest status and latency
+        self.latest_status = Some(result.status.clone());
+        self.latest_latency = Some(result.latency);
+
+        // Update history and make sure it's within the allowed length
+        self.latency_history
+            .push_back(result.latency.as_millis() as u64);
+
+        if self.latency_history.len() > MAX_LATENCY_HISTORY {
+            self.latency_history.pop_front();
+        };
+
+        // Update latency stats
+        self.latency.add(result.latency.as_millis() as u64);
+
+        // Update availability stats
+        let is_success = matches!(result.status, CheckStatus::Success { .. });
+        self.availability_stats.update(at, is_success);
+
+        let (health, reason) = assess_health(
+            at,
+            &result,
+            self.thresholds,
+            &mut self.baseline,
+            &config.anomaly_detection,
+        );
+        if self.health != Some(health) {
+            self.health_since = Some(at);
+        }
+        self.health = Some(health);
+        self.health_reason = reason.clone();
+
+        if health == Health::Down {
+            self.consecutive_failures += 1;
+        } else {
+            self.consecutive_failures = 0;
+        }
+
+        if result.cert_expiry.is_some() {
+            self.cert_expiry = result.cert_expiry;
+        }
+
+        // Open or resolve incidents
+        self.incidents.update(at, &result, health, &reason);
+
+        // Update flap detection
+        self.flap.update(health, &config.flap_detection);
+
+        // Update recent checks (push to the front and pop from the back
+        // so the recent logs are on top)
+        self.recent_checks.push_front((at, result, health));
+        if self.recent_checks.len() > MAX_LOG_LENGTH {
+            self.recent_checks.pop_back();
         };
     }
 
-    let sum: u64 = latency_history.iter().sum();
-    let len = latency_history.len() as u64;
+    /// The transition to notify about after the latest check, if any.
+    ///
+    /// Flapping endpoints would send a notification every other check, they're
+    /// only reported once they settle and only if they settled on a different
+    /// health than the one last notified. An endpoint that's up from its
+    /// first check isn't reported, one that's down or degraded is.
+    pub fn next_transition(&mut self, at: SystemTime) -> Option<Transition> {
+        let to = self.health?;
+        if self.flap.is_flapping() {
+            return None;
+        }
 
-    LatencyStats {
-        avg: Some(sum / len),
-        min: latency_history.iter().min().copied(),
-        max: latency_history.iter().max().copied(),
+        let transition = match self.notified_health {
+            Some((from, _)) if from == to => return None,
+            Some((from, since)) => Transition::new(self, Some(from), since, at),
+            None if to == Health::Up => None,
+            None => Transition::new(self, None, at, at),
+        };
+        self.notified_health = Some((to, at));
+        transition
     }
-}
 
-pub struct AvailabilityStats {
-    pub uptime_percent: Option<f64>,
-    pub number_of_checks: u64,
-    pub number_of_fails: u64,
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused && !self.paused {
+            self.availability_stats.pause(SystemTime::now());
+        }
+        self.paused = paused;
+    }
 }
 
-impl AvailabilityStats {
-    pub fn update(&mut self, is_success: bool) {
-        self.number_of_checks += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(up: bool) -> CheckResult {
+        CheckResult {
+            name: "api".to_string(),
+            status: if up {
+                CheckStatus::Success {
+                    code: 200,
+                    text: "OK".to_string(),
+                }
+            } else {
+                CheckStatus::Error {
+                    message: "Timeout".to_string(),
+                }
+            },
+            latency: Duration::from_millis(50),
+            cert_expiry: None,
+            generation: 0,
+        }
+    }
+
+    /// Records the checks a second apart and returns the transitions they caused.
+    fn run(state: &mut EndpointState, config: &StatuiConfig, checks: &[bool]) -> Vec<Transition> {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let offset = state.recent_checks.len() as u64;
+
+        let mut transitions = Vec::new();
+        for (i, up) in checks.iter().enumerate() {
+            let at = start + Duration::from_secs(offset + i as u64);
+            state.record(at, check(*up), config);
+            transitions.extend(state.next_transition(at));
+        }
+        transitions
+    }
+
+    fn endpoint() -> Endpoint {
+        Endpoint {
+            name: "api".to_string(),
+            url: "http://localhost".to_string(),
+            ..Endpoint::default()
+        }
+    }
+
+    #[test]
+    fn first_check_down_is_a_transition() {
+        let config = StatuiConfig::default();
+        let mut state = EndpointState::new(&endpoint(), &config);
+
+        let transitions = run(&mut state, &config, &[false]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].from, None);
+        assert_eq!(transitions[0].to, Health::Down);
+        assert_eq!(transitions[0].previous_label(), "UNKNOWN");
+    }
+
+    #[test]
+    fn first_check_up_is_not_a_transition() {
+        let config = StatuiConfig::default();
+        let mut state = EndpointState::new(&endpoint(), &config);
+
+        // Enough checks for the flap detector to settle
+        assert!(run(&mut state, &config, &[true; 21]).is_empty());
+        let transitions = run(&mut state, &config, &[false]);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].from, Some(Health::Up));
+    }
+
+    #[test]
+    fn reports_the_settled_health_once_flapping_ends() {
+        let mut config = StatuiConfig::default();
+        config.flap_detection.window = 3;
+        let mut state = EndpointState::new(&endpoint(), &config);
+
+        assert!(run(&mut state, &config, &[true, true, true]).is_empty());
+
+        // Going down makes it flap right away with such a short window
+        assert!(run(&mut state, &config, &[false, false]).is_empty());
+        assert!(state.flap.is_flapping());
+
+        let transitions = run(&mut state, &config, &[false]);
+        assert!(!state.flap.is_flapping());
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].from, Some(Health::Up));
+        assert_eq!(transitions[0].to, Health::Down);
+        assert_eq!(transitions[0].duration, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn flapping_back_to_the_notified_health_is_not_a_transition() {
+        let mut config = StatuiConfig::default();
+        config.flap_detection.window = 3;
+        let mut state = EndpointState::new(&endpoint(), &config);
+
+        run(&mut state, &config, &[true, true, true]);
+        assert!(run(&mut state, &config, &[false, true, true, true]).is_empty());
+    }
+
+    /// An app for `config` with nothing on disk, and the notifications it sends.
+    fn quiet_app(mut config: StatuiConfig) -> (App, Receiver<Notification>) {
+        config.history.enabled = false;
+        let (notify_tx, notify_rx) = mpsc::channel(8);
+        let mut app = App::new(&config, ConfigPaths::new(None), notify_tx);
+        app.silences = SilenceStore::open(None);
+        (app, notify_rx)
+    }
+
+    #[test]
+    fn silenced_endpoints_dont_notify_or_run_hooks() {
+        let mut config = StatuiConfig {
+            endpoints: vec![endpoint()],
+            ..StatuiConfig::default()
+        };
+        config.hooks.on_down = Some("true".to_string());
+
+        let (mut app, mut notify_rx) = quiet_app(config.clone());
+        app.silence("api", SilenceKind::Mute);
+        app.on_result(check(false));
+        assert!(notify_rx.try_recv().is_err());
+
+        let (mut app, mut notify_rx) = quiet_app(config);
+        app.on_result(check(false));
+        assert!(notify_rx.try_recv().unwrap().hook.is_some());
+    }
+
+    #[test]
+    fn changed_endpoints_stay_paused() {
+        let config = StatuiConfig {
+            endpoints: vec![
+                endpoint(),
+                Endpoint {
+                    name: "web".to_string(),
+                    ..endpoint()
+                },
+            ],
+            ..StatuiConfig::default()
+        };
+        let (mut app, _notify_rx) = quiet_app(config.clone());
+        app.set_paused("api", true);
 
-        if !is_success {
-            self.number_of_fails += 1;
+        let mut changed = config;
+        for endpoint in &mut changed.endpoints {
+            endpoint.url = "http://localhost:8080".to_string();
         }
+        let diff = app.apply_config(changed);
 
-        let number_of_successes = self.number_of_checks - self.number_of_fails;
-        self.uptime_percent =
-            Some((number_of_successes as f64 / self.number_of_checks as f64) * 100.0);
+        assert_eq!(diff.changed.len(), 2);
+        assert!(app.endpoint_states["api"].paused);
+        assert!(!app.endpoint_states["web"].paused);
     }
 }
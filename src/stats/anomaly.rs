@@ -0,0 +1,132 @@
+use std::time::SystemTime;
+
+use crate::config::AnomalyConfig;
+
+/// A learned latency baseline used to flag unusual latencies.
+///
+/// The baseline is an exponentially weighted moving average of the latency
+/// along with its variance, so it follows slow drifts but a sudden jump
+/// sticks out by several standard deviations.
+pub struct LatencyBaseline {
+    mean: f64,
+    variance: f64,
+    samples: u64,
+
+    // How many standard deviations the latest sample was from the baseline
+    deviation: Option<f64>,
+    anomaly_since: Option<SystemTime>,
+}
+
+impl LatencyBaseline {
+    pub fn new() -> Self {
+        LatencyBaseline {
+            mean: 0.0,
+            variance: 0.0,
+            samples: 0,
+            deviation: None,
+            anomaly_since: None,
+        }
+    }
+
+    /// Compares the sample against the baseline and then folds it in.
+    ///
+    /// Returns true if the sample is anomalous.
+    pub fn update(&mut self, at: SystemTime, latency_ms: u64, config: &AnomalyConfig) -> bool {
+        if !config.enabled {
+            self.anomaly_since = None;
+            return false;
+        }
+
+        let latency = latency_ms as f64;
+
+        if self.samples == 0 {
+            self.mean = latency;
+        } else {
+            let stddev = self.variance.sqrt().max(config.min_stddev_ms);
+            let deviation = (latency - self.mean) / stddev;
+            self.deviation = Some(deviation);
+
+            // Only slowdowns are anomalies, a faster response is good news
+            let warmed_up = self.samples >= config.warmup;
+            if warmed_up && deviation >= config.threshold {
+                self.anomaly_since.get_or_insert(at);
+            } else {
+                self.anomaly_since = None;
+            }
+
+            // EWMA of the mean and variance
+            let diff = latency - self.mean;
+            let increment = config.alpha * diff;
+            self.mean += increment;
+            self.variance = (1.0 - config.alpha) * (self.variance + diff * increment);
+        }
+
+        self.samples += 1;
+        self.is_anomalous()
+    }
+
+    /// Returns None until enough samples were seen to trust the baseline.
+    pub fn mean(&self, config: &AnomalyConfig) -> Option<f64> {
+        (config.enabled && self.samples >= config.warmup).then_some(self.mean)
+    }
+
+    pub fn stddev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+
+    /// Deviation of the latest sample from the baseline in standard deviations.
+    pub fn deviation(&self) -> Option<f64> {
+        self.deviation
+    }
+
+    pub fn anomaly_since(&self) -> Option<SystemTime> {
+        self.anomaly_since
+    }
+
+    pub fn is_anomalous(&self) -> bool {
+        self.anomaly_since.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warmed_up(config: &AnomalyConfig) -> LatencyBaseline {
+        let mut baseline = LatencyBaseline::new();
+        for i in 0..config.warmup {
+            // 95-105ms of jitter
+            baseline.update(SystemTime::now(), 95 + i % 11, config);
+        }
+        baseline
+    }
+
+    #[test]
+    fn flags_slow_responses() {
+        let config = AnomalyConfig::default();
+        let mut baseline = warmed_up(&config);
+
+        assert!(!baseline.update(SystemTime::now(), 104, &config));
+        assert!(baseline.update(SystemTime::now(), 400, &config));
+        assert!(baseline.deviation().unwrap() >= config.threshold);
+    }
+
+    #[test]
+    fn ignores_fast_responses() {
+        let config = AnomalyConfig::default();
+        let mut baseline = warmed_up(&config);
+
+        assert!(!baseline.update(SystemTime::now(), 1, &config));
+        assert!(baseline.deviation().unwrap() <= -config.threshold);
+    }
+
+    #[test]
+    fn waits_for_the_warmup() {
+        let config = AnomalyConfig::default();
+        let mut baseline = LatencyBaseline::new();
+
+        baseline.update(SystemTime::now(), 100, &config);
+        assert!(!baseline.update(SystemTime::now(), 5000, &config));
+        assert_eq!(baseline.mean(&config), None);
+    }
+}
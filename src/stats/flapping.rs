@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use crate::{config::FlapConfig, stats::incidents::Health};
+
+// Weights given to the oldest and newest state changes in the window,
+// recent changes count more so flapping is picked up (and cleared) faster.
+const OLDEST_WEIGHT: f64 = 0.8;
+const NEWEST_WEIGHT: f64 = 1.2;
+
+/// Detects endpoints that keep switching between healthy and unhealthy.
+///
+/// Works like Nagios flap detection: the last N health states are kept and
+/// a weighted percentage of state changes between them is computed. An
+/// endpoint starts flapping when that percentage goes over the high threshold
+/// and stops once it drops under the low one.
+pub struct FlapDetector {
+    states: VecDeque<Health>,
+    score: f64,
+    flapping: bool,
+}
+
+impl FlapDetector {
+    pub fn new() -> Self {
+        FlapDetector {
+            states: VecDeque::new(),
+            score: 0.0,
+            flapping: false,
+        }
+    }
+
+    pub fn update(&mut self, health: Health, config: &FlapConfig) {
+        if !config.enabled {
+            self.flapping = false;
+            return;
+        }
+
+        self.states.push_back(health);
+        while self.states.len() > config.window.max(2) {
+            self.states.pop_front();
+        }
+
+        self.score = state_change_percent(&self.states);
+
+        if self.flapping && self.score < config.low_threshold {
+            self.flapping = false;
+        } else if !self.flapping && self.score > config.high_threshold {
+            self.flapping = true;
+        }
+    }
+
+    /// Weighted percentage of state changes in the window (0-100).
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    pub fn is_flapping(&self) -> bool {
+        self.flapping
+    }
+}
+
+fn state_change_percent(states: &VecDeque<Health>) -> f64 {
+    let transitions = states.len().saturating_sub(1);
+    if transitions == 0 {
+        return 0.0;
+    }
+
+    let step = if transitions > 1 {
+        (NEWEST_WEIGHT - OLDEST_WEIGHT) / (transitions - 1) as f64
+    } else {
+        0.0
+    };
+
+    let changed: f64 = states
+        .iter()
+        .zip(states.iter().skip(1))
+        .enumerate()
+        .filter(|(_, (previous, current))| previous != current)
+        .map(|(i, _)| OLDEST_WEIGHT + step * i as f64)
+        .sum();
+
+    changed / transitions as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(detector: &mut FlapDetector, config: &FlapConfig, healths: &[Health]) {
+        for health in healths {
+            detector.update(*health, config);
+        }
+    }
+
+    #[test]
+    fn steady_endpoints_dont_flap() {
+        let config = FlapConfig::default();
+        let mut detector = FlapDetector::new();
+
+        run(&mut detector, &config, &[Health::Up; 30]);
+        assert!(!detector.is_flapping());
+        assert_eq!(detector.score(), 0.0);
+
+        // A single outage is a couple of changes, not flapping
+        run(&mut detector, &config, &[Health::Down; 3]);
+        run(&mut detector, &config, &[Health::Up]);
+        assert!(!detector.is_flapping());
+    }
+
+    #[test]
+    fn flaps_until_it_settles_under_the_low_threshold() {
+        let config = FlapConfig::default();
+        let mut detector = FlapDetector::new();
+
+        run(&mut detector, &config, &[Health::Up; 21]);
+        for _ in 0..4 {
+            run(&mut detector, &config, &[Health::Down, Health::Up]);
+        }
+        assert!(detector.is_flapping());
+        assert!(detector.score() > config.high_threshold);
+
+        // Still flapping between the thresholds
+        while detector.score() >= config.low_threshold {
+            assert!(detector.is_flapping());
+            detector.update(Health::Up, &config);
+        }
+        assert!(!detector.is_flapping());
+    }
+
+    #[test]
+    fn recent_changes_weigh_more() {
+        let oldest: VecDeque<Health> = [Health::Down, Health::Up, Health::Up].into();
+        let newest: VecDeque<Health> = [Health::Up, Health::Up, Health::Down].into();
+
+        assert!(state_change_percent(&newest) > state_change_percent(&oldest));
+    }
+
+    #[test]
+    fn disabled_never_flaps() {
+        let config = FlapConfig {
+            enabled: false,
+            ..FlapConfig::default()
+        };
+        let mut detector = FlapDetector::new();
+
+        for _ in 0..20 {
+            run(&mut detector, &config, &[Health::Down, Health::Up]);
+        }
+        assert!(!detector.is_flapping());
+    }
+}
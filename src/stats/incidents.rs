@@ -0,0 +1,259 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{CheckResult, CheckStatus};
+
+// Only the most recent incidents are kept around per endpoint,
+// the MTTR/MTBF counters still cover all of them.
+const MAX_RESOLVED_INCIDENTS: usize = 50;
+
+/// The health of an endpoint as seen by a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Health {
+    Up,
+    Degraded,
+    Down,
+}
+
+impl Health {
+    /// Errors and 5xx responses are Down, 4xx responses are Degraded
+    /// (the service answers but something's off), anything else is Up.
+    pub fn of(result: &CheckResult) -> Health {
+        match &result.status {
+            CheckStatus::Error { .. } => Health::Down,
+            CheckStatus::Success { code, .. } if *code >= 500 => Health::Down,
+            CheckStatus::Success { code, .. } if *code >= 400 => Health::Degraded,
+            CheckStatus::Success { .. } => Health::Up,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Health::Up => "UP",
+            Health::Degraded => "DEGRADED",
+            Health::Down => "DOWN",
+        }
+    }
+}
+
+/// An outage of an endpoint, from the first unhealthy check
+/// to the first healthy one after it.
+#[derive(Debug, Clone)]
+pub struct Incident {
+    pub endpoint: String,
+    pub start: SystemTime,
+    pub end: Option<SystemTime>,
+    pub first_error: String,
+    pub check_count: u64,
+    // The worst health seen during the incident
+    pub worst: Health,
+}
+
+impl Incident {
+    pub fn is_open(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// How long the incident lasted, or has lasted so far if it's still open.
+    pub fn duration(&self, now: SystemTime) -> Duration {
+        self.end
+            .unwrap_or(now)
+            .duration_since(self.start)
+            .unwrap_or_default()
+    }
+}
+
+/// Derives incidents from the health transitions of an endpoint.
+pub struct IncidentTracker {
+    pub open: Option<Incident>,
+    pub resolved: VecDeque<Incident>,
+
+    pub incident_count: u64,
+    total_repair_time: Duration,
+    resolved_count: u64,
+    total_time_between: Duration,
+    between_count: u64,
+    last_resolved_at: Option<SystemTime>,
+}
+
+impl IncidentTracker {
+    pub fn new() -> Self {
+        IncidentTracker {
+            open: None,
+            resolved: VecDeque::new(),
+            incident_count: 0,
+            total_repair_time: Duration::ZERO,
+            resolved_count: 0,
+            total_time_between: Duration::ZERO,
+            between_count: 0,
+            last_resolved_at: None,
+        }
+    }
+
+    /// Feeds the health of a check to the tracker, 'reason' describes
+    /// what's wrong and is only used when a new incident is opened.
+    pub fn update(&mut self, at: SystemTime, result: &CheckResult, health: Health, reason: &str) {
+        match (&mut self.open, health) {
+            // Still healthy, nothing to do
+            (None, Health::Up) => {}
+
+            // Things went south, open a new incident
+            (None, health) => {
+                if let Some(last_resolved_at) = self.last_resolved_at {
+                    self.total_time_between +=
+                        at.duration_since(last_resolved_at).unwrap_or_default();
+                    self.between_count += 1;
+                }
+
+                self.incident_count += 1;
+                self.open = Some(Incident {
+                    endpoint: result.name.clone(),
+                    start: at,
+                    end: None,
+                    first_error: reason.to_string(),
+                    check_count: 1,
+                    worst: health,
+                });
+            }
+
+            // Recovered, resolve the open incident
+            (Some(_), Health::Up) => {
+                let Some(mut incident) = self.open.take() else {
+                    return;
+                };
+                incident.end = Some(at);
+
+                self.total_repair_time += incident.duration(at);
+                self.resolved_count += 1;
+                self.last_resolved_at = Some(at);
+
+                self.resolved.push_front(incident);
+                if self.resolved.len() > MAX_RESOLVED_INCIDENTS {
+                    self.resolved.pop_back();
+                }
+            }
+
+            // Still failing
+            (Some(incident), health) => {
+                incident.check_count += 1;
+                incident.worst = incident.worst.max(health);
+            }
+        }
+    }
+
+    /// Mean time to recovery over the resolved incidents.
+    pub fn mttr(&self) -> Option<Duration> {
+        (self.resolved_count > 0).then(|| self.total_repair_time / self.resolved_count as u32)
+    }
+
+    /// Mean time between failures, from the end of an incident
+    /// to the start of the next one.
+    pub fn mtbf(&self) -> Option<Duration> {
+        (self.between_count > 0).then(|| self.total_time_between / self.between_count as u32)
+    }
+
+    /// All incidents, the open one (if any) first then the most recent.
+    pub fn all(&self) -> impl Iterator<Item = &Incident> {
+        self.open.iter().chain(self.resolved.iter())
+    }
+}
+
+/// Short description of a check status for logs and incidents.
+pub fn describe(status: &CheckStatus) -> String {
+    match status {
+        CheckStatus::Success { code, text } => format!("{code} {text}"),
+        CheckStatus::Error { message } => message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn result(code: u16) -> CheckResult {
+        CheckResult {
+            name: "api".to_string(),
+            status: CheckStatus::Success {
+                code,
+                text: String::new(),
+            },
+            latency: Duration::from_millis(10),
+            cert_expiry: None,
+            generation: 0,
+        }
+    }
+
+    /// Feeds one check a minute, the code of each check picks its health.
+    fn track(tracker: &mut IncidentTracker, start: SystemTime, codes: &[u16]) {
+        for (i, code) in codes.iter().enumerate() {
+            let result = result(*code);
+            let health = Health::of(&result);
+            tracker.update(start + MINUTE * i as u32, &result, health, "went wrong");
+        }
+    }
+
+    #[test]
+    fn opens_and_resolves_incidents() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut tracker = IncidentTracker::new();
+
+        track(&mut tracker, start, &[200, 429, 503, 500]);
+        let open = tracker.open.as_ref().unwrap();
+        assert_eq!(open.start, start + MINUTE);
+        assert_eq!(open.check_count, 3);
+        assert_eq!(open.worst, Health::Down);
+        assert_eq!(open.first_error, "went wrong");
+        assert_eq!(tracker.mttr(), None);
+
+        track(&mut tracker, start + 4 * MINUTE, &[200]);
+        assert!(tracker.open.is_none());
+        assert_eq!(tracker.resolved[0].end, Some(start + 4 * MINUTE));
+        assert_eq!(tracker.resolved[0].duration(start), 3 * MINUTE);
+        assert_eq!(tracker.incident_count, 1);
+    }
+
+    #[test]
+    fn computes_mttr_and_mtbf() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut tracker = IncidentTracker::new();
+
+        // Down for 1 minute, up for 3, down for 3 then up for 1
+        track(
+            &mut tracker,
+            start,
+            &[500, 200, 200, 200, 500, 500, 500, 200],
+        );
+
+        assert_eq!(tracker.incident_count, 2);
+        assert_eq!(tracker.mttr(), Some(2 * MINUTE));
+        assert_eq!(tracker.mtbf(), Some(3 * MINUTE));
+
+        // An open incident counts towards MTBF but not yet MTTR
+        track(&mut tracker, start + 12 * MINUTE, &[500]);
+        assert_eq!(tracker.mttr(), Some(2 * MINUTE));
+        assert_eq!(tracker.mtbf(), Some(4 * MINUTE));
+        assert_eq!(tracker.all().count(), 3);
+        assert!(tracker.all().next().unwrap().is_open());
+    }
+
+    #[test]
+    fn keeps_only_the_latest_resolved_incidents() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut tracker = IncidentTracker::new();
+
+        let codes: Vec<u16> = [500, 200].repeat(MAX_RESOLVED_INCIDENTS + 5);
+        track(&mut tracker, start, &codes);
+
+        assert_eq!(tracker.resolved.len(), MAX_RESOLVED_INCIDENTS);
+        assert_eq!(tracker.incident_count, MAX_RESOLVED_INCIDENTS as u64 + 5);
+        // Every incident still counts towards the MTTR
+        assert_eq!(tracker.mttr(), Some(MINUTE));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+
+use hdrhistogram::Histogram;
+
+// Histogram bounds in milliseconds. Anything above the highest trackable
+// value is clamped to it, which is way past any sane timeout anyway.
+const LOWEST_TRACKABLE_MS: u64 = 1;
+const HIGHEST_TRACKABLE_MS: u64 = 10 * 60 * 1000;
+
+// 2 significant figures keeps the error within 1% while keeping
+// each histogram at a few KB.
+const SIGNIFICANT_FIGURES: u8 = 2;
+
+// The rolling window is made of fixed size slots, once the newest slot
+// is full a new one is started and the oldest is dropped. This way
+// the window covers the last (WINDOW_SLOTS - 1) * SLOT_SIZE to
+// WINDOW_SLOTS * SLOT_SIZE samples without keeping every sample around.
+const WINDOW_SLOTS: usize = 10;
+const SLOT_SIZE: u64 = 10;
+
+/// A summary of latency samples in milliseconds.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatencyStats {
+    pub avg: Option<u64>,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub p50: Option<u64>,
+    pub p90: Option<u64>,
+    pub p95: Option<u64>,
+    pub p99: Option<u64>,
+    pub stddev: Option<f64>,
+}
+
+impl LatencyStats {
+    fn from_histogram(histogram: &Histogram<u32>) -> Self {
+        if histogram.is_empty() {
+            return LatencyStats::default();
+        }
+
+        LatencyStats {
+            avg: Some(histogram.mean().round() as u64),
+            min: Some(histogram.min()),
+            max: Some(histogram.max()),
+            p50: Some(histogram.value_at_quantile(0.50)),
+            p90: Some(histogram.value_at_quantile(0.90)),
+            p95: Some(histogram.value_at_quantile(0.95)),
+            p99: Some(histogram.value_at_quantile(0.99)),
+            stddev: Some(histogram.stdev()),
+        }
+    }
+}
+
+/// Streaming latency tracker backed by HDR histograms.
+///
+/// Keeps a lifetime histogram of every sample recorded and a rolling
+/// window over the most recent samples.
+pub struct LatencyTracker {
+    lifetime: Histogram<u32>,
+    window_slots: VecDeque<Histogram<u32>>,
+
+    lifetime_stats: LatencyStats,
+    window_stats: LatencyStats,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        LatencyTracker {
+            lifetime: new_histogram(),
+            window_slots: VecDeque::from([new_histogram()]),
+            lifetime_stats: LatencyStats::default(),
+            window_stats: LatencyStats::default(),
+        }
+    }
+
+    /// Records a latency sample (in milliseconds) without refreshing the
+    /// stats, so lots of samples can be replayed at once. Call `refresh`
+    /// once they're all in.
+    pub fn add(&mut self, latency_ms: u64) {
+        self.lifetime.saturating_record(latency_ms);
+
+        if self
+            .window_slots
+            .back()
+            .is_none_or(|slot| slot.len() >= SLOT_SIZE)
+        {
+            self.window_slots.push_back(new_histogram());
+            if self.window_slots.len() > WINDOW_SLOTS {
+                self.window_slots.pop_front();
+            }
+        }
+
+        if let Some(slot) = self.window_slots.back_mut() {
+            slot.saturating_record(latency_ms);
+        }
+    }
+
+    /// Recomputes the stats from the histograms.
+    pub fn refresh(&mut self) {
+        self.lifetime_stats = LatencyStats::from_histogram(&self.lifetime);
+        self.window_stats = LatencyStats::from_histogram(&self.merged_window());
+    }
+
+    /// Stats over every sample since statui started.
+    pub fn lifetime(&self) -> &LatencyStats {
+        &self.lifetime_stats
+    }
+
+    /// Stats over the most recent samples.
+    pub fn window(&self) -> &LatencyStats {
+        &self.window_stats
+    }
+
+    fn merged_window(&self) -> Histogram<u32> {
+        let mut merged = new_histogram();
+        for slot in &self.window_slots {
+            // All the slots share the same bounds so adding can't fail
+            let _ = merged.add(slot);
+        }
+        merged
+    }
+}
+
+fn new_histogram() -> Histogram<u32> {
+    Histogram::new_with_bounds(
+        LOWEST_TRACKABLE_MS,
+        HIGHEST_TRACKABLE_MS,
+        SIGNIFICANT_FIGURES,
+    )
+    .expect("Histogram bounds are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_within_1_percent(actual: Option<u64>, expected: u64) {
+        let actual = actual.expect("a value") as f64;
+        let expected = expected as f64;
+        assert!(
+            (actual - expected).abs() <= expected / 100.0,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn computes_percentiles() {
+        let mut tracker = LatencyTracker::new();
+        for latency in 1..=1000 {
+            tracker.add(latency);
+        }
+        tracker.refresh();
+
+        let stats = tracker.lifetime();
+        assert_eq!(stats.min, Some(1));
+        assert_within_1_percent(stats.max, 1000);
+        assert_within_1_percent(stats.avg, 500);
+        assert_within_1_percent(stats.p50, 500);
+        assert_within_1_percent(stats.p90, 900);
+        assert_within_1_percent(stats.p95, 950);
+        assert_within_1_percent(stats.p99, 990);
+    }
+
+    #[test]
+    fn the_window_forgets_old_samples() {
+        let mut tracker = LatencyTracker::new();
+        for _ in 0..100 {
+            tracker.add(2000);
+        }
+        // Exactly fills the window
+        for _ in 0..WINDOW_SLOTS as u64 * SLOT_SIZE {
+            tracker.add(20);
+        }
+        tracker.refresh();
+
+        assert_within_1_percent(tracker.window().max, 20);
+        assert_within_1_percent(tracker.window().p99, 20);
+        assert_within_1_percent(tracker.lifetime().max, 2000);
+        assert_within_1_percent(tracker.lifetime().p50, 20);
+    }
+
+    #[test]
+    fn stats_wait_for_a_refresh() {
+        let mut tracker = LatencyTracker::new();
+        assert_eq!(tracker.window().p50, None);
+
+        tracker.add(100);
+        assert_eq!(tracker.window().p50, None);
+
+        tracker.refresh();
+        assert_within_1_percent(tracker.window().p50, 100);
+    }
+
+    #[test]
+    fn clamps_huge_latencies() {
+        let mut tracker = LatencyTracker::new();
+        tracker.add(u64::MAX);
+        tracker.refresh();
+
+        assert_within_1_percent(tracker.lifetime().max, HIGHEST_TRACKABLE_MS);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod anomaly;
+pub mod flapping;
+pub mod incidents;
+pub mod latency;
+pub mod slo;
+pub mod uptime;
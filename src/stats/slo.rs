@@ -0,0 +1,144 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    config::SloConfig,
+    stats::uptime::{AvailabilityStats, combined_uptime_percent},
+};
+
+// Error budgets and burn rates.
+//
+// The error budget is the downtime an SLO allows over its window, e.g. 0.1%
+// of 30 days for 99.9%. The burn rate is how fast it's being used compared
+// to using it evenly over the window: a burn rate of 1 runs out exactly at
+// the end of the window, 14.4 burns 2% of a 30 day budget every hour.
+
+const SHORT_BURN_WINDOW: Duration = Duration::from_secs(60 * 60);
+const LONG_BURN_WINDOW: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Where an endpoint stands against its SLO.
+#[derive(Debug, Clone, Copy)]
+pub struct SloStatus {
+    pub slo: SloConfig,
+
+    // Percentage of the error budget used and left, remaining goes
+    // negative once the budget is blown.
+    pub budget_consumed: f64,
+    pub budget_remaining: f64,
+
+    pub burn_rate_1h: Option<f64>,
+    pub burn_rate_6h: Option<f64>,
+}
+
+impl SloStatus {
+    /// Computes the SLO status from the availability stats of the endpoints
+    /// it covers, a single one or every endpoint of a group.
+    ///
+    /// Returns None if there's no data in the SLO window yet.
+    pub fn compute(slo: SloConfig, stats: &[&AvailabilityStats], now: SystemTime) -> Option<Self> {
+        let uptime = combined_uptime_percent(stats.iter().copied(), slo.window.duration(), now)?;
+
+        // The target is below 100 (checked when the config is loaded),
+        // so there's always some budget
+        let budget = 100.0 - slo.target;
+        let downtime = 100.0 - uptime;
+        let budget_consumed = downtime / budget * 100.0;
+
+        let burn_rate = |window: Duration| {
+            let uptime = combined_uptime_percent(stats.iter().copied(), window, now)?;
+            Some((100.0 - uptime) / budget)
+        };
+
+        Some(SloStatus {
+            slo,
+            budget_consumed,
+            budget_remaining: 100.0 - budget_consumed,
+            burn_rate_1h: burn_rate(SHORT_BURN_WINDOW),
+            burn_rate_6h: burn_rate(LONG_BURN_WINDOW),
+        })
+    }
+
+    /// True when both burn windows are over the fast burn rate.
+    ///
+    /// Requiring both means a short blip doesn't count (the 6h rate stays
+    /// low) and a recovered endpoint stops counting soon (the 1h rate drops).
+    pub fn is_fast_burn(&self) -> bool {
+        let over = |rate: Option<f64>| rate.is_some_and(|rate| rate >= self.slo.fast_burn);
+        over(self.burn_rate_1h) && over(self.burn_rate_6h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::uptime::UptimeWindow;
+
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    fn slo() -> SloConfig {
+        SloConfig {
+            target: 99.0,
+            window: UptimeWindow::Month,
+            fast_burn: 14.4,
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("a value");
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    /// Up for the first 9 of the last 10 hours, then down.
+    fn down_for_the_last_hour(now: SystemTime) -> AvailabilityStats {
+        let mut stats = AvailabilityStats::new();
+        stats.update(now - 10 * HOUR, true);
+        stats.update(now - HOUR, false);
+        stats
+    }
+
+    fn always_up(now: SystemTime) -> AvailabilityStats {
+        let mut stats = AvailabilityStats::new();
+        stats.update(now - 10 * HOUR, true);
+        stats
+    }
+
+    #[test]
+    fn computes_the_budget_and_burn_rates() {
+        let now = SystemTime::now();
+        let stats = down_for_the_last_hour(now);
+
+        let status = SloStatus::compute(slo(), &[&stats], now).unwrap();
+
+        // 90% uptime against a 1% budget
+        assert_close(Some(status.budget_consumed), 1000.0);
+        assert_close(Some(status.budget_remaining), -900.0);
+        assert_close(status.burn_rate_1h, 100.0);
+        assert_close(status.burn_rate_6h, 100.0 / 6.0);
+        assert!(status.is_fast_burn());
+    }
+
+    #[test]
+    fn combines_the_endpoints_of_a_group() {
+        let now = SystemTime::now();
+        let down = down_for_the_last_hour(now);
+        let up = always_up(now);
+
+        let status = SloStatus::compute(slo(), &[&down, &up], now).unwrap();
+
+        // 19 of the 20 monitored hours were up
+        assert_close(Some(status.budget_consumed), 500.0);
+        assert_close(status.burn_rate_1h, 50.0);
+        assert_close(status.burn_rate_6h, 100.0 / 12.0);
+        assert!(!status.is_fast_burn());
+    }
+
+    #[test]
+    fn needs_data_in_the_window() {
+        let stats = AvailabilityStats::new();
+        assert!(SloStatus::compute(slo(), &[&stats], SystemTime::now()).is_none());
+        assert!(SloStatus::compute(slo(), &[], SystemTime::now()).is_none());
+    }
+}
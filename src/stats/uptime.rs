@@ -0,0 +1,300 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The rolling windows uptime is reported over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum UptimeWindow {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl UptimeWindow {
+    pub const ALL: [UptimeWindow; 4] = [
+        UptimeWindow::Hour,
+        UptimeWindow::Day,
+        UptimeWindow::Week,
+        UptimeWindow::Month,
+    ];
+
+    pub fn duration(&self) -> Duration {
+        const HOUR: u64 = 60 * 60;
+        match self {
+            UptimeWindow::Hour => Duration::from_secs(HOUR),
+            UptimeWindow::Day => Duration::from_secs(24 * HOUR),
+            UptimeWindow::Week => Duration::from_secs(7 * 24 * HOUR),
+            UptimeWindow::Month => Duration::from_secs(30 * 24 * HOUR),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UptimeWindow::Hour => "1h",
+            UptimeWindow::Day => "24h",
+            UptimeWindow::Week => "7d",
+            UptimeWindow::Month => "30d",
+        }
+    }
+}
+
+impl fmt::Display for UptimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl TryFrom<String> for UptimeWindow {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        UptimeWindow::ALL
+            .into_iter()
+            .find(|window| window.label() == value)
+            .ok_or_else(|| format!("Unknown uptime window '{value}', expected 1h, 24h, 7d or 30d"))
+    }
+}
+
+impl From<UptimeWindow> for String {
+    fn from(window: UptimeWindow) -> Self {
+        window.label().to_string()
+    }
+}
+
+/// A stretch of time the endpoint was continuously up or down.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: SystemTime,
+    end: SystemTime,
+    up: bool,
+}
+
+/// Availability of an endpoint.
+///
+/// Uptime is weighted by time rather than by number of checks: the status
+/// seen by a check is assumed to hold until the next check. This makes
+/// endpoints checked on different intervals comparable.
+pub struct AvailabilityStats {
+    pub number_of_checks: u64,
+    pub number_of_fails: u64,
+
+    // Closed segments, consecutive segments with the same status are merged
+    segments: VecDeque<Segment>,
+    // The latest check, its status holds until the next check comes in
+    last_check: Option<(SystemTime, bool)>,
+}
+
+impl AvailabilityStats {
+    pub fn new() -> Self {
+        AvailabilityStats {
+            number_of_checks: 0,
+            number_of_fails: 0,
+            segments: VecDeque::new(),
+            last_check: None,
+        }
+    }
+
+    pub fn update(&mut self, at: SystemTime, is_success: bool) {
+        self.number_of_checks += 1;
+
+        if !is_success {
+            self.number_of_fails += 1;
+        }
+
+        self.close_open_segment(at);
+        self.last_check = Some((at, is_success));
+        self.prune(at);
+    }
+
+    /// Stops the latest status from holding any longer, used when the
+    /// endpoint is paused so the paused time doesn't count either way.
+    pub fn pause(&mut self, at: SystemTime) {
+        self.close_open_segment(at);
+        self.last_check = None;
+    }
+
+    /// Percentage of the monitored time in the window the endpoint was up.
+    ///
+    /// Returns None if there's no data in the window.
+    pub fn uptime_percent(&self, window: UptimeWindow, now: SystemTime) -> Option<f64> {
+        self.uptime_percent_over(window.duration(), now)
+    }
+
+    /// Same as `uptime_percent` but over any duration up to the largest window.
+    pub fn uptime_percent_over(&self, duration: Duration, now: SystemTime) -> Option<f64> {
+        combined_uptime_percent([self], duration, now)
+    }
+
+    /// The time the endpoint was up and monitored over the last `duration`.
+    fn monitored_time(&self, duration: Duration, now: SystemTime) -> (Duration, Duration) {
+        let window_start = now.checked_sub(duration).unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let mut up_time = Duration::ZERO;
+        let mut total_time = Duration::ZERO;
+
+        for segment in self.segments.iter().chain(self.open_segment(now).iter()) {
+            let start = segment.start.max(window_start);
+            let Ok(covered) = segment.end.duration_since(start) else {
+                continue;
+            };
+
+            total_time += covered;
+            if segment.up {
+                up_time += covered;
+            }
+        }
+
+        (up_time, total_time)
+    }
+
+    fn open_segment(&self, now: SystemTime) -> Option<Segment> {
+        self.last_check.map(|(start, up)| Segment {
+            start,
+            end: now,
+            up,
+        })
+    }
+
+    fn close_open_segment(&mut self, at: SystemTime) {
+        let Some((start, up)) = self.last_check else {
+            return;
+        };
+
+        if let Some(last) = self.segments.back_mut()
+            && last.up == up
+            && last.end == start
+        {
+            last.end = at;
+            return;
+        }
+
+        self.segments.push_back(Segment { start, end: at, up });
+    }
+
+    /// Drops segments that fell out of the largest window.
+    fn prune(&mut self, now: SystemTime) {
+        let Some(oldest) = now.checked_sub(UptimeWindow::Month.duration()) else {
+            return;
+        };
+
+        while self.segments.front().is_some_and(|s| s.end < oldest) {
+            self.segments.pop_front();
+        }
+    }
+}
+
+/// Uptime of several endpoints taken together over the last `duration`,
+/// e.g. for a group. Every endpoint weighs as much as the time it was monitored.
+///
+/// Returns None if there's no data in the window.
+pub fn combined_uptime_percent<'a>(
+    stats: impl IntoIterator<Item = &'a AvailabilityStats>,
+    duration: Duration,
+    now: SystemTime,
+) -> Option<f64> {
+    let mut up_time = Duration::ZERO;
+    let mut total_time = Duration::ZERO;
+    let mut latest = Vec::new();
+
+    for stats in stats {
+        let (up, total) = stats.monitored_time(duration, now);
+        up_time += up;
+        total_time += total;
+        latest.extend(stats.open_segment(now).map(|segment| segment.up));
+    }
+
+    if total_time.is_zero() {
+        // Checks that just came in, all we know is their status
+        if latest.is_empty() {
+            return None;
+        }
+        let up = latest.iter().filter(|up| **up).count();
+        return Some(up as f64 / latest.len() as f64 * 100.0);
+    }
+
+    Some(up_time.as_secs_f64() / total_time.as_secs_f64() * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn weighs_uptime_by_time() {
+        let now = SystemTime::now();
+        let mut stats = AvailabilityStats::new();
+
+        // One failed check out of four, but down for half of the time
+        stats.update(now - 4 * HOUR, true);
+        stats.update(now - 3 * HOUR, true);
+        stats.update(now - 2 * HOUR, false);
+        stats.update(now - HOUR / 2, true);
+
+        assert_eq!(stats.uptime_percent(UptimeWindow::Day, now), Some(62.5));
+        assert_eq!(stats.number_of_fails, 1);
+        // Consecutive ups are merged into a single segment
+        assert_eq!(stats.segments.len(), 2);
+    }
+
+    #[test]
+    fn splits_segments_at_the_window_start() {
+        let now = SystemTime::now();
+        let mut stats = AvailabilityStats::new();
+
+        stats.update(now - 2 * HOUR, true);
+        stats.update(now - HOUR / 2, false);
+
+        assert_eq!(stats.uptime_percent(UptimeWindow::Hour, now), Some(50.0));
+        assert_eq!(stats.uptime_percent(UptimeWindow::Day, now), Some(75.0));
+    }
+
+    #[test]
+    fn paused_time_doesnt_count() {
+        let now = SystemTime::now();
+        let mut stats = AvailabilityStats::new();
+
+        stats.update(now - 5 * HOUR, true);
+        stats.pause(now - 4 * HOUR);
+        stats.update(now - HOUR, false);
+
+        assert_eq!(stats.uptime_percent(UptimeWindow::Day, now), Some(50.0));
+    }
+
+    #[test]
+    fn prunes_segments_out_of_the_largest_window() {
+        let now = SystemTime::now();
+        let mut stats = AvailabilityStats::new();
+
+        stats.update(now - 40 * DAY, false);
+        stats.update(now - 35 * DAY, true);
+        stats.update(now - 5 * DAY, true);
+        stats.update(now, true);
+
+        assert_eq!(stats.segments.len(), 1);
+        assert_eq!(stats.uptime_percent(UptimeWindow::Month, now), Some(100.0));
+    }
+
+    #[test]
+    fn needs_a_check_in_the_window() {
+        let now = SystemTime::now();
+        let mut stats = AvailabilityStats::new();
+        assert_eq!(stats.uptime_percent(UptimeWindow::Hour, now), None);
+
+        // All there is to go by is the status of the check that just came in
+        stats.update(now, false);
+        assert_eq!(stats.uptime_percent(UptimeWindow::Hour, now), Some(0.0));
+
+        stats.pause(now);
+        let later = now + 2 * HOUR;
+        assert_eq!(stats.uptime_percent(UptimeWindow::Hour, later), None);
+    }
+}
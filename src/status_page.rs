@@ -0,0 +1,391 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+
+use crate::{
+    cli::StatusPageArgs,
+    config::{ConfigPaths, Endpoint, StatuiConfig},
+    history::{self, CheckRecord},
+    state,
+    stats::{
+        anomaly::LatencyBaseline,
+        incidents::{Health, Incident, IncidentTracker},
+    },
+    ui::util,
+};
+
+// Static HTML status page.
+//
+// The page is rebuilt from the check history on disk, so it can be
+// written by `statui status-page` or kept up to date by headless mode
+// and then served by anything that serves files.
+
+const DEFAULT_TEMPLATE: &str = include_str!("status_page.html");
+const PAGE_FILE_NAME: &str = "index.html";
+
+// Below this uptime a day is shown as down instead of degraded
+const DEGRADED_UPTIME: f64 = 0.99;
+
+/// The checks of an endpoint on a single (UTC) day.
+#[derive(Debug, Clone, Copy, Default)]
+struct DayStats {
+    checks: u64,
+    degraded: u64,
+    down: u64,
+}
+
+impl DayStats {
+    fn uptime(&self) -> Option<f64> {
+        (self.checks > 0).then(|| 1.0 - self.down as f64 / self.checks as f64)
+    }
+
+    fn class(&self) -> &'static str {
+        match self.uptime() {
+            None => "none",
+            Some(uptime) if uptime < DEGRADED_UPTIME => "down",
+            Some(_) if self.down > 0 || self.degraded > 0 => "degraded",
+            Some(_) => "up",
+        }
+    }
+}
+
+/// An endpoint as it's shown on the page.
+struct EndpointSummary<'a> {
+    endpoint: &'a Endpoint,
+    baseline: LatencyBaseline,
+    incidents: IncidentTracker,
+    health: Option<Health>,
+    last_check: Option<SystemTime>,
+    // Oldest day first
+    days: Vec<DayStats>,
+}
+
+impl EndpointSummary<'_> {
+    /// The current health, None if there's no recent check to go by.
+    fn health(&self, config: &StatuiConfig, now: SystemTime) -> Option<Health> {
+        let interval = self.endpoint.interval.unwrap_or(config.default_interval);
+        let timeout = self.endpoint.timeout.unwrap_or(config.default_timeout);
+        let max_age = Duration::from_secs(2 * interval + timeout);
+
+        let last_check = self.last_check?;
+        if now.duration_since(last_check).unwrap_or_default() > max_age {
+            return None;
+        }
+        self.health
+    }
+
+    fn uptime(&self) -> Option<f64> {
+        let checks: u64 = self.days.iter().map(|day| day.checks).sum();
+        let down: u64 = self.days.iter().map(|day| day.down).sum();
+        (checks > 0).then(|| 1.0 - down as f64 / checks as f64)
+    }
+}
+
+/// Runs `statui status-page`, writing the page to the output directory.
+pub fn run(args: &StatusPageArgs) -> Result<()> {
+    let config = StatuiConfig::load(&ConfigPaths::new(args.config.clone()))?;
+
+    let Some(out) = args.out.as_ref().or(config.status_page.out.as_ref()) else {
+        bail!("No output directory, pass --out or set `out` in the [status_page] block");
+    };
+
+    let path = write(&config, out)?;
+    println!("Status page written to {}", path.display());
+    Ok(())
+}
+
+/// Renders the page from the history and writes it to `out/index.html`,
+/// returns the path of the page.
+pub fn write(config: &StatuiConfig, out: &Path) -> Result<PathBuf> {
+    if !config.history.enabled {
+        bail!("History is disabled in the config, the status page is built from it");
+    }
+
+    // Older days would show up as gaps, the history is already gone
+    if config.status_page.days > config.history.retention_days {
+        bail!(
+            "The status page shows {} days but the history only keeps {}, raise retention_days in [history]",
+            config.status_page.days,
+            config.history.retention_days
+        );
+    }
+
+    let template = match &config.status_page.template {
+        Some(path) => fs::read_to_string(path)
+            .wrap_err_with(|| format!("Couldn't read the template {}", path.display()))?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    let now = SystemTime::now();
+    let days = config.status_page.days.max(1);
+    let since = now
+        .checked_sub(Duration::from_secs(days * 24 * 60 * 60))
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let records = history::load_since(&config.history, since);
+
+    let page = render(config, &records, &template, now);
+
+    // Written next to the page then renamed so it's never served half written
+    fs::create_dir_all(out)?;
+    let path = out.join(PAGE_FILE_NAME);
+    let tmp_path = out.join(format!(".{PAGE_FILE_NAME}.tmp"));
+    fs::write(&tmp_path, page)?;
+    fs::rename(&tmp_path, &path)?;
+
+    Ok(path)
+}
+
+/// Fills the template with the groups, incidents and overall health.
+fn render(
+    config: &StatuiConfig,
+    records: &[CheckRecord],
+    template: &str,
+    now: SystemTime,
+) -> String {
+    let summaries = summarize(config, records, now);
+
+    let healths: Vec<Option<Health>> = summaries
+        .iter()
+        .map(|summary| summary.health(config, now))
+        .collect();
+    let (overall, overall_class) = match healths.iter().flatten().max() {
+        Some(Health::Down) => ("Major outage", "down"),
+        Some(Health::Degraded) => ("Some systems are degraded", "degraded"),
+        Some(Health::Up) => ("All systems operational", "up"),
+        None => ("No recent data", "unknown"),
+    };
+
+    let updated: DateTime<Utc> = now.into();
+    let values = [
+        ("title", util::xml_escape(&config.status_page.title)),
+        ("overall", overall.to_string()),
+        ("overall_class", overall_class.to_string()),
+        ("groups", render_groups(config, &summaries, now)),
+        ("incidents", render_incidents(config, &summaries, now)),
+        ("updated", updated.format("%Y-%m-%d %H:%M UTC").to_string()),
+        ("days", config.status_page.days.to_string()),
+    ];
+
+    util::fill_placeholders(template, &values)
+}
+
+/// Replays the history of every endpoint and counts its checks per day.
+///
+/// Only the health and incidents are tracked, the latency stats the TUI
+/// keeps are too slow to rebuild from months of checks.
+fn summarize<'a>(
+    config: &'a StatuiConfig,
+    records: &[CheckRecord],
+    now: SystemTime,
+) -> Vec<EndpointSummary<'a>> {
+    let days = config.status_page.days.max(1) as usize;
+    let today = DateTime::<Utc>::from(now).date_naive();
+
+    let mut summaries: Vec<EndpointSummary> = config
+        .endpoints
+        .iter()
+        .map(|endpoint| EndpointSummary {
+            endpoint,
+            baseline: LatencyBaseline::new(),
+            incidents: IncidentTracker::new(),
+            health: None,
+            last_check: None,
+            days: vec![DayStats::default(); days],
+        })
+        .collect();
+    let index: HashMap<&str, usize> = config
+        .endpoints
+        .iter()
+        .enumerate()
+        .map(|(i, endpoint)| (endpoint.name.as_str(), i))
+        .collect();
+
+    for record in records {
+        let Some(summary) = index.get(record.name.as_str()).map(|i| &mut summaries[*i]) else {
+            continue;
+        };
+
+        let at = record.time();
+        let result = record.to_result();
+        let (health, reason) = state::assess_health(
+            at,
+            &result,
+            config.latency_thresholds(summary.endpoint),
+            &mut summary.baseline,
+            &config.anomaly_detection,
+        );
+        summary.incidents.update(at, &result, health, &reason);
+        summary.health = Some(health);
+        summary.last_check = Some(at);
+
+        let date: NaiveDate = DateTime::<Utc>::from(at).date_naive();
+        let age = (today - date).num_days();
+        if age < 0 || age as usize >= days {
+            continue;
+        }
+        let day = &mut summary.days[days - 1 - age as usize];
+        day.checks += 1;
+        match health {
+            Health::Down => day.down += 1,
+            Health::Degraded => day.degraded += 1,
+            Health::Up => {}
+        }
+    }
+
+    summaries
+}
+
+/// One section per group in the order they first show up in the config,
+/// the endpoints without a group come first.
+fn render_groups(config: &StatuiConfig, summaries: &[EndpointSummary], now: SystemTime) -> String {
+    let mut groups: Vec<Option<&str>> = Vec::new();
+    for summary in summaries {
+        let group = summary.endpoint.group.as_deref();
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    groups.sort_by_key(|group| group.is_some());
+
+    let mut out = String::new();
+    for group in groups {
+        let _ = writeln!(
+            out,
+            "<h2>{}</h2>",
+            util::xml_escape(group.unwrap_or("Services"))
+        );
+        let _ = writeln!(out, r#"<section class="group">"#);
+
+        for summary in summaries
+            .iter()
+            .filter(|summary| summary.endpoint.group.as_deref() == group)
+        {
+            render_endpoint(&mut out, config, summary, now);
+        }
+
+        let _ = writeln!(out, "</section>");
+    }
+    out
+}
+
+fn render_endpoint(
+    out: &mut String,
+    config: &StatuiConfig,
+    summary: &EndpointSummary,
+    now: SystemTime,
+) {
+    let (health, class) = match summary.health(config, now) {
+        Some(Health::Up) => ("Operational", "up"),
+        Some(Health::Degraded) => ("Degraded", "degraded"),
+        Some(Health::Down) => ("Down", "down"),
+        None => ("No recent data", "unknown"),
+    };
+
+    let _ = writeln!(out, r#"  <div class="endpoint">"#);
+    let _ = writeln!(
+        out,
+        r#"    <div class="endpoint-header"><span class="name">{}</span><span class="health {class}">{health}</span></div>"#,
+        util::xml_escape(&summary.endpoint.name)
+    );
+
+    let today = DateTime::<Utc>::from(now).date_naive();
+    let _ = write!(out, r#"    <div class="bars">"#);
+    for (i, day) in summary.days.iter().enumerate() {
+        let date = today - chrono::Duration::days((summary.days.len() - 1 - i) as i64);
+        let title = match day.uptime() {
+            Some(uptime) => format!("{date}: {:.2}% uptime", uptime * 100.0),
+            None => format!("{date}: no data"),
+        };
+        let _ = write!(
+            out,
+            r#"<span class="bar {}" title="{title}"></span>"#,
+            day.class()
+        );
+    }
+    let _ = writeln!(out, "</div>");
+
+    let uptime = summary
+        .uptime()
+        .map(|uptime| format!("{:.2}% uptime", uptime * 100.0))
+        .unwrap_or_else(|| "No data".to_string());
+    let _ = writeln!(
+        out,
+        r#"    <div class="legend"><span>{} days ago</span><span>{uptime}</span><span>Today</span></div>"#,
+        summary.days.len()
+    );
+    let _ = writeln!(out, "  </div>");
+}
+
+/// The most recent incidents of every endpoint, newest first.
+fn render_incidents(
+    config: &StatuiConfig,
+    summaries: &[EndpointSummary],
+    now: SystemTime,
+) -> String {
+    let mut incidents: Vec<&Incident> = summaries
+        .iter()
+        .flat_map(|summary| summary.incidents.all())
+        .collect();
+    incidents.sort_by_key(|incident| std::cmp::Reverse(incident.start));
+    incidents.truncate(config.status_page.incidents);
+
+    if incidents.is_empty() {
+        return format!(
+            r#"<p class="empty">No incidents in the last {} days.</p>"#,
+            config.status_page.days
+        );
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<ul class="incidents">"#);
+    for incident in incidents {
+        let start: DateTime<Utc> = incident.start.into();
+        let class = if incident.worst == Health::Down {
+            "down"
+        } else {
+            "degraded"
+        };
+        let duration = util::format_duration(incident.duration(now));
+        let duration = if incident.is_open() {
+            format!("Ongoing for {duration}")
+        } else {
+            format!("Lasted {duration}")
+        };
+
+        let _ = writeln!(
+            out,
+            r#"  <li class="incident {class}"><span class="when">{}</span><br><strong>{}</strong> {} <span class="duration">{duration}</span></li>"#,
+            start.format("%Y-%m-%d %H:%M UTC"),
+            util::xml_escape(&incident.endpoint),
+            util::xml_escape(&incident.first_error)
+        );
+    }
+    let _ = writeln!(out, "</ul>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_the_history_of_every_day_shown() {
+        let mut config = StatuiConfig::default();
+        assert_eq!(config.status_page.days, 90);
+        assert!(config.status_page.days <= config.history.retention_days);
+
+        config.history.retention_days = 30;
+        let err = write(&config, Path::new("unused")).unwrap_err();
+        assert!(err.to_string().starts_with("The status page shows 90 days"));
+    }
+}
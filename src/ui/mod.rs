@@ -1,32 +1,33 @@
 pub mod fx;
 mod theme;
-mod util;
+pub mod util;
 mod widgets;
 
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
 };
 use std::time::Duration;
 
 use crate::{
-    state::{App, AppMode},
+    state::{App, AppMode, View},
     ui::fx::FxManager,
 };
 use widgets::{
-    footer::render_footer, inspector::render_inspector, table::render_table,
+    alerts::render_alerts,
+    footer::render_footer,
+    form::{render_delete_confirm, render_form, render_prompt},
+    incidents::render_incidents,
+    inspector::render_inspector,
+    table::render_table,
     welcome::render_welcome_message,
 };
 
 /// Renders the UI widgets for the application.
-pub fn render_ui(
-    frame: &mut Frame,
-    mut app: &mut App,
-    fx_manager: &mut FxManager,
-    elapsed: Duration,
-) {
+pub fn render_ui(frame: &mut Frame, app: &mut App, fx_manager: &mut FxManager, elapsed: Duration) {
     if app.endpoint_order.is_empty() {
         render_welcome_message(frame);
+        render_overlays(frame, app);
         return;
     }
 
@@ -38,6 +39,25 @@ pub fn render_ui(
     let workspace_area = root[0];
     let footer_area = root[1];
 
+    match app.view {
+        View::Endpoints => render_endpoints(frame, app, fx_manager, elapsed, workspace_area),
+        View::Incidents => render_incidents(frame, app, workspace_area),
+        View::Alerts => render_alerts(frame, app, workspace_area),
+    }
+
+    render_footer(frame, app, footer_area);
+
+    render_overlays(frame, app);
+}
+
+/// Renders the endpoints table and the inspector next to it when it's open.
+fn render_endpoints(
+    frame: &mut Frame,
+    app: &mut App,
+    fx_manager: &mut FxManager,
+    elapsed: Duration,
+    area: Rect,
+) {
     let constraints = match app.mode {
         AppMode::Normal => vec![Constraint::Percentage(100)],
         AppMode::Inspecting => vec![Constraint::Percentage(50), Constraint::Percentage(50)],
@@ -46,14 +66,23 @@ pub fn render_ui(
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(constraints)
-        .split(workspace_area);
+        .split(area);
 
-    render_table(frame, &mut app, chunks[0]);
+    render_table(frame, app, chunks[0]);
 
     if app.mode == AppMode::Inspecting {
-        render_inspector(frame, &mut app, chunks[1]);
+        render_inspector(frame, app, chunks[1]);
         fx_manager.render_inspector(frame, chunks[1], elapsed.into());
     }
+}
 
-    render_footer(frame, footer_area);
+/// Renders the popups that are drawn on top of everything else.
+fn render_overlays(frame: &mut Frame, app: &App) {
+    if let Some(form) = &app.form {
+        render_form(frame, form);
+    } else if let Some(prompt) = &app.prompt {
+        render_prompt(frame, prompt);
+    } else if let Some(name) = &app.pending_delete {
+        render_delete_confirm(frame, name);
+    }
 }
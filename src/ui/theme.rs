@@ -5,10 +5,7 @@ use ratatui::{
     symbols::border,
 };
 
-// I should probably move these somewhere else but I'll
-// keep them here for now
-pub const VERY_POOR_LATENCY: u128 = 500;
-pub const POOR_LATENCY: u128 = 200;
+use crate::config::LatencyThresholds;
 
 pub const MAIN_COLOR: Color = Color::Cyan;
 // pub const MAIN_COLOR: Color = Color::from_u32(0xa53359);
@@ -57,17 +54,50 @@ impl Theme {
     pub const STATUS_REDIRECT: Color = Color::Blue;
     pub const STATUS_WARN: Color = Color::Yellow;
     pub const STATUS_ERROR: Color = Color::Red;
-
-    pub fn latency_color(latency: &Duration) -> Color {
-        if latency.as_millis() > VERY_POOR_LATENCY {
+    pub const STATUS_PAUSED: Color = Color::DarkGray;
+    pub const STATUS_FLAPPING: Color = Color::Magenta;
+    pub const STATUS_ANOMALY: Color = Color::LightRed;
+    pub const STATUS_SILENCED: Color = Color::LightBlue;
+
+    pub const PAUSED_SYMBOL: &str = "⏸";
+    pub const FLAPPING_SYMBOL: &str = "↯";
+    pub const FAST_BURN_SYMBOL: &str = "▲";
+    pub const ACK_SYMBOL: &str = "✔";
+    pub const SNOOZE_SYMBOL: &str = "☾";
+    pub const MUTE_SYMBOL: &str = "⊘";
+
+    /// Colors a latency by the endpoint's warn/critical thresholds
+    pub fn latency_color(latency: &Duration, thresholds: &LatencyThresholds) -> Color {
+        let latency_ms = latency.as_millis() as u64;
+        if thresholds.is_critical(latency_ms) {
             Theme::STATUS_ERROR
-        } else if latency.as_millis() > POOR_LATENCY {
+        } else if thresholds.is_warn(latency_ms) {
             Theme::STATUS_WARN
         } else {
             Theme::STATUS_OK
         }
     }
 
+    /// 99%+ is Green, 90-99% is Yellow, below is Red
+    pub fn uptime_color(uptime: Option<f64>) -> Color {
+        match uptime {
+            Some(uptime) if uptime >= 99.0 => Theme::STATUS_OK,
+            Some(uptime) if uptime >= 90.0 => Theme::STATUS_WARN,
+            Some(_) => Theme::STATUS_ERROR,
+            None => Theme::BORDER_UNFOCUSED,
+        }
+    }
+
+    /// Over a quarter of the error budget left is Green, some left is Yellow, none is Red
+    pub fn budget_color(remaining: Option<f64>) -> Color {
+        match remaining {
+            Some(remaining) if remaining >= 25.0 => Theme::STATUS_OK,
+            Some(remaining) if remaining > 0.0 => Theme::STATUS_WARN,
+            Some(_) => Theme::STATUS_ERROR,
+            None => Theme::BORDER_UNFOCUSED,
+        }
+    }
+
     pub fn color_code(code: &u16) -> Color {
         match code {
             200..=299 => Theme::STATUS_OK,
@@ -1,10 +1,18 @@
+use std::time::{Duration, SystemTime};
+
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
 };
 
-use crate::{backend::CheckStatus, ui::theme::Theme};
+use crate::{
+    alerts::AlertState,
+    backend::CheckStatus,
+    silence::{Silence, SilenceKind},
+    stats::incidents::Health,
+    ui::theme::Theme,
+};
 
 /// Helper function to create a centered rectangle
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -57,11 +65,139 @@ pub fn wrap_with_brackets(title: &str, title_style: Style, bracket_style: Style)
     ])
 }
 
+/// Helper function to format a duration in a short human readable way (e.g. 2h 5m).
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes, seconds) = (
+        secs / 86_400,
+        (secs % 86_400) / 3_600,
+        (secs % 3_600) / 60,
+        secs % 60,
+    );
+
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Replaces the {{placeholders}} of a template in a single pass, so a
+/// placeholder that shows up in a filled in value is left alone.
+///
+/// Unknown placeholders are kept as they are.
+pub fn fill_placeholders(template: &str, values: &[(&str, String)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        let value = after.find("}}").and_then(|end| {
+            let key = &after[..end];
+            let (_, value) = values.iter().find(|(k, _)| *k == key)?;
+            Some((value, end))
+        });
+
+        match value {
+            Some((value, end)) => {
+                text.push_str(value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                text.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+
+    text.push_str(rest);
+    text
+}
+
+/// Helper function to get the color for a given health.
+pub fn get_health_color(health: Health) -> Color {
+    match health {
+        Health::Up => Theme::STATUS_OK,
+        Health::Degraded => Theme::STATUS_WARN,
+        Health::Down => Theme::STATUS_ERROR,
+    }
+}
+
+/// Helper function to get the color for a given alert state.
+pub fn get_alert_color(state: AlertState) -> Color {
+    match state {
+        AlertState::Pending => Theme::STATUS_WARN,
+        AlertState::Firing => Theme::STATUS_ERROR,
+        AlertState::Resolved => Theme::STATUS_OK,
+    }
+}
+
 /// Helper function to get the color for a given status based on the code.
-pub fn get_status_color(status: &Option<CheckStatus>) -> Color {
+pub fn get_status_color(status: &Option<CheckStatus>, paused: bool) -> Color {
+    if paused {
+        return Theme::STATUS_PAUSED;
+    }
+
     match status {
         Some(CheckStatus::Success { code, .. }) => Theme::color_code(code),
         Some(CheckStatus::Error { .. }) => Theme::STATUS_ERROR,
         None => Theme::BORDER_UNFOCUSED,
     }
 }
+
+/// Short badge for an acked, snoozed or muted endpoint (e.g. "☾ 25m").
+pub fn silence_badge(silence: &Silence, now: SystemTime) -> String {
+    match silence.kind {
+        SilenceKind::Ack { .. } => format!("{} ACK", Theme::ACK_SYMBOL),
+        SilenceKind::Snooze { .. } => {
+            let left = silence
+                .until()
+                .and_then(|until| until.duration_since(now).ok())
+                .unwrap_or_default();
+            format!("{} {}", Theme::SNOOZE_SYMBOL, format_duration(left))
+        }
+        SilenceKind::Mute => format!("{} MUTED", Theme::MUTE_SYMBOL),
+    }
+}
+
+/// Escapes text to put in XML or HTML, in elements as well as in quoted attributes.
+pub fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_xml() {
+        assert_eq!(
+            xml_escape(r#"<a href="x?y=1&z='2'">"#),
+            "&lt;a href=&quot;x?y=1&amp;z=&apos;2&apos;&quot;&gt;"
+        );
+        assert_eq!(xml_escape("&amp;"), "&amp;amp;");
+        assert_eq!(xml_escape("plain"), "plain");
+    }
+
+    #[test]
+    fn fills_each_placeholder_once() {
+        let values = [
+            ("title", "{{days}} of {{title}}".to_string()),
+            ("days", "30".to_string()),
+        ];
+
+        let text = fill_placeholders("<h1>{{title}}</h1><p>{{days}} {{unknown}} {{", &values);
+        assert_eq!(text, "<h1>{{days}} of {{title}}</h1><p>30 {{unknown}} {{");
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    layout::Constraint,
+    prelude::*,
+    widgets::{Block, Cell, Row, Table},
+};
+
+use crate::{
+    alerts::AlertState,
+    state::App,
+    ui::{theme::Theme, util},
+};
+
+/// Renders the alerts of every rule, firing ones first.
+pub fn render_alerts(frame: &mut Frame, app: &mut App, chunk: Rect) {
+    let header = Row::new(vec![
+        Line::from("STATE").centered(),
+        Line::from("RULE").centered(),
+        Line::from("ENDPOINT").centered(),
+        Line::from("SINCE").centered(),
+        Line::from("FOR").centered(),
+        Line::from("DETAILS").centered(),
+    ])
+    .style(
+        Style::default()
+            .fg(Theme::BORDER_FOCUSED)
+            .add_modifier(Modifier::BOLD)
+            .add_modifier(Modifier::UNDERLINED),
+    );
+
+    let rows = create_rows(app);
+
+    let widths = vec![
+        Constraint::Percentage(10),
+        Constraint::Percentage(18),
+        Constraint::Percentage(18),
+        Constraint::Percentage(12),
+        Constraint::Percentage(10),
+        Constraint::Percentage(32),
+    ];
+
+    let title = util::wrap_with_brackets(
+        &format!("Alerts ({} firing)", app.alerts.firing_count()),
+        Theme::table_header(),
+        Theme::table_border_style(),
+    );
+
+    let block = Block::bordered()
+        .border_set(Theme::PANEL_BORDER)
+        .border_style(Theme::table_border_style())
+        .title(title)
+        .title_alignment(Alignment::Left);
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .highlight_symbol(Theme::HIGHLIGHT_SYMBOL)
+        .row_highlight_style(Theme::table_highlight());
+
+    frame.render_stateful_widget(table, chunk, &mut app.alerts_table_state);
+}
+
+fn create_rows(app: &App) -> Vec<Row<'static>> {
+    let now = SystemTime::now();
+
+    app.alerts()
+        .into_iter()
+        .map(|alert| {
+            // Firing alerts count from when they fired, the others from
+            // when their condition started matching.
+            let since = match alert.state {
+                AlertState::Pending => alert.pending_since,
+                AlertState::Firing | AlertState::Resolved => {
+                    alert.firing_since.unwrap_or(alert.pending_since)
+                }
+            };
+            let until = alert.resolved_at.unwrap_or(now);
+            let duration = until.duration_since(since).unwrap_or_default();
+
+            // Older alerts get the date as well
+            let since: DateTime<Local> = since.into();
+            let since_fmt = if since.date_naive() == Local::now().date_naive() {
+                "%H:%M:%S"
+            } else {
+                "%m-%d %H:%M"
+            };
+
+            Row::new(vec![
+                Cell::from(Line::from(alert.state.label()).centered())
+                    .style(Style::default().fg(util::get_alert_color(alert.state))),
+                Cell::from(alert.rule.clone()),
+                Cell::from(alert.endpoint.clone()),
+                Cell::from(Line::from(since.format(since_fmt).to_string()).centered()),
+                Cell::from(Line::from(util::format_duration(duration)).centered()),
+                Cell::from(alert.message.clone())
+                    .style(Style::default().fg(Theme::INSPECTOR_TEXT_FG)),
+            ])
+            .height(1)
+        })
+        .collect()
+}
@@ -1,20 +1,52 @@
+use std::time::Duration;
+
 use ratatui::{
     Frame,
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Paragraph},
 };
 
-use crate::ui::theme::Theme;
+use crate::{
+    state::{App, StatusLevel, View},
+    ui::theme::Theme,
+};
+
+// How long info messages stay in the status line, errors stay until replaced.
+const INFO_MESSAGE_TTL: Duration = Duration::from_secs(5);
 
-pub fn render_footer(frame: &mut Frame, area: Rect) {
-    let controls = [
-        ("q", "Quit"),
-        ("j/k", "Up/Down"),
-        ("i", "Inspect"),
-        // ("/", "Filter"),
-        // ("?", "Help"),
-    ];
+pub fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let controls: &[(&str, &str)] = match app.view {
+        View::Endpoints => &[
+            ("q", "Quit"),
+            ("j/k", "Up/Down"),
+            ("i", "Inspect"),
+            ("r", "Check Now"),
+            ("p/P", "Pause/All"),
+            ("a/e/d", "Add/Edit/Delete"),
+            ("A/z/m", "Ack/Snooze/Mute"),
+            ("x", "Export"),
+            ("2/3", "Incidents/Alerts"),
+            // ("/", "Filter"),
+            // ("?", "Help"),
+        ],
+        View::Incidents => &[
+            ("q", "Quit"),
+            ("j/k", "Up/Down"),
+            ("Enter", "Go to Endpoint"),
+            ("1", "Endpoints"),
+            ("3", "Alerts"),
+        ],
+        View::Alerts => &[
+            ("q", "Quit"),
+            ("j/k", "Up/Down"),
+            ("Enter", "Go to Endpoint"),
+            ("A/z/m", "Ack/Snooze/Mute"),
+            ("1", "Endpoints"),
+            ("2", "Incidents"),
+        ],
+    };
 
     let spans: Vec<Span> = controls
         .iter()
@@ -33,9 +65,37 @@ pub fn render_footer(frame: &mut Frame, area: Rect) {
         })
         .collect();
 
-    let footer = Paragraph::new(Line::from(spans))
+    let controls_line = Line::from(spans);
+    let controls_width = controls_line.width() as u16;
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(controls_width), Constraint::Min(0)])
+        .split(area);
+
+    let footer = Paragraph::new(controls_line)
         .alignment(Alignment::Left)
         .block(Block::default());
 
-    frame.render_widget(footer, area);
+    frame.render_widget(footer, chunks[0]);
+
+    if let Some(status_line) = create_status_line(app) {
+        let status = Paragraph::new(status_line).alignment(Alignment::Right);
+        frame.render_widget(status, chunks[1]);
+    }
+}
+
+fn create_status_line(app: &App) -> Option<Line<'static>> {
+    let message = app.status_message.as_ref()?;
+
+    let color = match message.level {
+        StatusLevel::Info if message.created_at.elapsed() > INFO_MESSAGE_TTL => return None,
+        StatusLevel::Info => Theme::DESC_FG,
+        StatusLevel::Error => Theme::STATUS_ERROR,
+    };
+
+    Some(Line::from(Span::styled(
+        format!("{} ", message.text),
+        Style::default().fg(color),
+    )))
 }
@@ -0,0 +1,151 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use crate::{
+    form::{EndpointForm, FORM_FIELDS, PromptKind, SilencePrompt},
+    ui::{theme::Theme, util},
+};
+
+// Width of the field labels so the inputs line up
+const LABEL_WIDTH: usize = 10;
+
+/// Renders the add/edit endpoint form as a popup over the rest of the ui.
+pub fn render_form(frame: &mut Frame, form: &EndpointForm) {
+    let mut lines: Vec<Line> = FORM_FIELDS
+        .iter()
+        .enumerate()
+        .map(|(i, label)| create_field_line(label, &form.values[i], i == form.focused))
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Interval/Timeout in seconds, empty uses the defaults",
+        Theme::footer_desc(),
+    )));
+    lines.push(Line::from(Span::styled(
+        "Headers as 'Key: Value; Other-Key: Value'",
+        Theme::footer_desc(),
+    )));
+    lines.push(Line::from(""));
+
+    if let Some(error) = &form.error {
+        lines.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Theme::STATUS_ERROR),
+        )));
+    }
+
+    lines.push(create_hint_line(&[
+        ("Tab", "Next"),
+        ("Enter", "Save"),
+        ("Esc", "Cancel"),
+    ]));
+
+    let area = util::centered_area(lines.len() as u16 + 2, 70, frame.area());
+    render_popup(frame, &form.title(), lines, area);
+}
+
+/// Renders the confirmation popup shown before deleting an endpoint.
+pub fn render_delete_confirm(frame: &mut Frame, name: &str) {
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("Delete "),
+            Span::styled(
+                name.to_owned(),
+                Style::default()
+                    .fg(Theme::STATUS_ERROR)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" from the config file?"),
+        ])
+        .centered(),
+        Line::from(""),
+        create_hint_line(&[("y", "Delete"), ("any", "Cancel")]).centered(),
+    ];
+
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 4;
+    let area = util::centered_area(lines.len() as u16 + 2, width, frame.area());
+    render_popup(frame, "Delete Endpoint", lines, area);
+}
+
+/// Renders the prompt asking for an ack note or a snooze duration.
+pub fn render_prompt(frame: &mut Frame, prompt: &SilencePrompt) {
+    let help = match prompt.kind {
+        PromptKind::Ack => "Optional, silences notifications until it recovers",
+        PromptKind::Snooze => "Like 30m, 2h or 1d",
+    };
+
+    let mut lines = vec![
+        create_field_line(prompt.label(), &prompt.value, true),
+        Line::from(""),
+        Line::from(Span::styled(help, Theme::footer_desc())),
+        Line::from(""),
+    ];
+
+    if let Some(error) = &prompt.error {
+        lines.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Theme::STATUS_ERROR),
+        )));
+    }
+
+    lines.push(create_hint_line(&[("Enter", "Save"), ("Esc", "Cancel")]));
+
+    let area = util::centered_area(lines.len() as u16 + 2, 70, frame.area());
+    render_popup(frame, &prompt.title(), lines, area);
+}
+
+fn render_popup(frame: &mut Frame, title: &str, lines: Vec<Line<'static>>, area: Rect) {
+    let title = util::wrap_with_brackets(title, Theme::table_header(), Theme::table_border_style());
+
+    let block = Block::bordered()
+        .border_set(Theme::PANEL_BORDER)
+        .border_style(Theme::table_border_style())
+        .title(title)
+        .title_alignment(Alignment::Left);
+
+    let popup = Paragraph::new(lines).block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup, area);
+}
+
+fn create_field_line(label: &str, value: &str, focused: bool) -> Line<'static> {
+    let label_style = if focused {
+        Theme::table_header()
+    } else {
+        Style::default().fg(Theme::INSPECTOR_TEXT_FG)
+    };
+
+    // A block cursor at the end of the focused field
+    let cursor = if focused { "█" } else { "" };
+
+    Line::from(vec![
+        Span::styled(
+            format!(" {:<LABEL_WIDTH$}", format!("{label}:")),
+            label_style,
+        ),
+        Span::raw(value.to_owned()),
+        Span::styled(cursor, Style::default().fg(Theme::BORDER_FOCUSED)),
+    ])
+}
+
+fn create_hint_line(hints: &[(&str, &str)]) -> Line<'static> {
+    let spans: Vec<Span> = hints
+        .iter()
+        .flat_map(|(key, desc)| {
+            vec![
+                Span::styled(format!(" {} ", key), Theme::footer_keys()),
+                Span::styled(format!(" {} ", desc), Theme::footer_desc()),
+                Span::from(" "),
+            ]
+        })
+        .collect();
+
+    Line::from(spans)
+}
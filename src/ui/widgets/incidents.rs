@@ -0,0 +1,100 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    layout::Constraint,
+    prelude::*,
+    widgets::{Block, Cell, Row, Table},
+};
+
+use crate::{
+    state::App,
+    ui::{theme::Theme, util},
+};
+
+/// Renders every open and resolved incident across all endpoints.
+pub fn render_incidents(frame: &mut Frame, app: &mut App, chunk: Rect) {
+    let header = Row::new(vec![
+        Line::from("STATE").centered(),
+        Line::from("ENDPOINT").centered(),
+        Line::from("STARTED").centered(),
+        Line::from("DURATION").centered(),
+        Line::from("CHECKS").centered(),
+        Line::from("FIRST ERROR").centered(),
+    ])
+    .style(
+        Style::default()
+            .fg(Theme::BORDER_FOCUSED)
+            .add_modifier(Modifier::BOLD)
+            .add_modifier(Modifier::UNDERLINED),
+    );
+
+    let rows = create_rows(app);
+
+    let widths = vec![
+        Constraint::Percentage(12),
+        Constraint::Percentage(22),
+        Constraint::Percentage(16),
+        Constraint::Percentage(12),
+        Constraint::Percentage(8),
+        Constraint::Percentage(30),
+    ];
+
+    let open_count = app.incidents().iter().filter(|i| i.is_open()).count();
+    let title = util::wrap_with_brackets(
+        &format!("Incidents ({} open)", open_count),
+        Theme::table_header(),
+        Theme::table_border_style(),
+    );
+
+    let block = Block::bordered()
+        .border_set(Theme::PANEL_BORDER)
+        .border_style(Theme::table_border_style())
+        .title(title)
+        .title_alignment(Alignment::Left);
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .highlight_symbol(Theme::HIGHLIGHT_SYMBOL)
+        .row_highlight_style(Theme::table_highlight());
+
+    frame.render_stateful_widget(table, chunk, &mut app.incidents_table_state);
+}
+
+fn create_rows(app: &App) -> Vec<Row<'static>> {
+    let now = SystemTime::now();
+
+    app.incidents()
+        .into_iter()
+        .map(|incident| {
+            let (state, state_color) = if incident.is_open() {
+                (
+                    format!("OPEN {}", incident.worst.label()),
+                    util::get_health_color(incident.worst),
+                )
+            } else {
+                ("RESOLVED".to_string(), Theme::STATUS_OK)
+            };
+
+            // Older incidents get the date as well
+            let started: DateTime<Local> = incident.start.into();
+            let started_fmt = if started.date_naive() == Local::now().date_naive() {
+                "%H:%M:%S"
+            } else {
+                "%m-%d %H:%M"
+            };
+
+            Row::new(vec![
+                Cell::from(Line::from(state).centered()).style(Style::default().fg(state_color)),
+                Cell::from(incident.endpoint.clone()),
+                Cell::from(Line::from(started.format(started_fmt).to_string()).centered()),
+                Cell::from(Line::from(util::format_duration(incident.duration(now))).centered()),
+                Cell::from(Line::from(incident.check_count.to_string()).centered()),
+                Cell::from(incident.first_error.clone())
+                    .style(Style::default().fg(Theme::INSPECTOR_TEXT_FG)),
+            ])
+            .height(1)
+        })
+        .collect()
+}
@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime};
+
 use ratatui::{
     Frame,
     layout::Rect,
@@ -8,12 +10,18 @@ use ratatui::{
 
 use crate::{
     state::EndpointState,
+    stats::{slo::SloStatus, uptime::UptimeWindow},
     ui::{theme::Theme, util},
 };
 
-pub fn render_availability_stats(endpoint_state: &EndpointState, frame: &mut Frame, area: Rect) {
+pub fn render_availability_stats(
+    endpoint_state: &EndpointState,
+    slo: Option<SloStatus>,
+    frame: &mut Frame,
+    area: Rect,
+) {
     let block = Block::new();
-    let avail_stats_lines = create_avail_stats_lines(endpoint_state);
+    let avail_stats_lines = create_avail_stats_lines(endpoint_state, slo);
     let h = avail_stats_lines.len() as u16;
     let w = avail_stats_lines
         .iter()
@@ -26,19 +34,12 @@ pub fn render_availability_stats(endpoint_state: &EndpointState, frame: &mut Fra
     frame.render_widget(par, util::centered_area(h, w, area));
 }
 
-fn create_avail_stats_lines(endpoint_state: &EndpointState) -> Vec<Line<'static>> {
+fn create_avail_stats_lines(
+    endpoint_state: &EndpointState,
+    slo: Option<SloStatus>,
+) -> Vec<Line<'static>> {
     let stats = &endpoint_state.availability_stats;
-
-    // Calculate Uptime Color
-    // 99%+ is Green, 90-99% is Yellow, below is Red
-    let uptime_val = stats.uptime_percent.unwrap_or(0.0);
-    let uptime_color = if uptime_val >= 99.0 {
-        Theme::STATUS_OK
-    } else if uptime_val >= 90.0 {
-        Theme::STATUS_WARN
-    } else {
-        Theme::STATUS_ERROR
-    };
+    let now = SystemTime::now();
 
     // Calculate Fails Color
     // 0 Fails is Green, >0 is Red
@@ -48,43 +49,183 @@ fn create_avail_stats_lines(endpoint_state: &EndpointState) -> Vec<Line<'static>
         Theme::STATUS_OK
     };
 
-    let uptime_str = match stats.uptime_percent {
-        Some(uptime_val) => format!("{:.1}%", uptime_val),
+    let mut lines: Vec<Line> = UptimeWindow::ALL
+        .iter()
+        .map(|window| {
+            let uptime = stats.uptime_percent(*window, now);
+            let uptime_str = match uptime {
+                Some(uptime_val) => format!("{:.2}%", uptime_val),
+                None => "-".to_string(),
+            };
+
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<8}", format!("{}:", window.label())),
+                    Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+                ),
+                Span::styled(
+                    uptime_str,
+                    Style::default()
+                        .fg(Theme::uptime_color(uptime))
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ])
+            .left_aligned()
+        })
+        .collect();
+
+    lines.extend([
+        Line::from(vec![
+            Span::styled(
+                format!("{:<8}", "Checks:"),
+                Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+            ),
+            // No style for the number of checks
+            Span::raw(stats.number_of_checks.to_string()),
+        ])
+        .left_aligned(),
+        Line::from(vec![
+            Span::styled(
+                format!("{:<8}", "Fails:"),
+                Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+            ),
+            Span::styled(
+                stats.number_of_fails.to_string(),
+                Style::default().fg(fail_color),
+            ),
+        ])
+        .left_aligned(),
+    ]);
+
+    // Flap score, highlighted while the endpoint is flapping
+    let flap = &endpoint_state.flap;
+    let (flap_str, flap_color) = if flap.is_flapping() {
+        (
+            format!("{:.1}% {}", flap.score(), Theme::FLAPPING_SYMBOL),
+            Theme::STATUS_FLAPPING,
+        )
+    } else {
+        (format!("{:.1}%", flap.score()), Theme::INSPECTOR_TEXT_FG)
+    };
+
+    lines.push(
+        Line::from(vec![
+            Span::styled(
+                format!("{:<8}", "Flap:"),
+                Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+            ),
+            Span::styled(flap_str, Style::default().fg(flap_color)),
+        ])
+        .left_aligned(),
+    );
+
+    // Incident stats
+    let incidents = &endpoint_state.incidents;
+    let format_mean = |mean: Option<Duration>| match mean {
+        Some(mean) => util::format_duration(mean),
         None => "-".to_string(),
     };
 
-    vec![
+    lines.extend([
         Line::from(vec![
             Span::styled(
-                format!("{:<8}", "Uptime:"),
+                format!("{:<11}", "Incidents:"),
                 Style::default().fg(Theme::INSPECTOR_TEXT_FG),
             ),
             Span::styled(
-                uptime_str,
-                Style::default()
-                    .fg(uptime_color)
-                    .add_modifier(Modifier::BOLD),
+                incidents.incident_count.to_string(),
+                Style::default().fg(if incidents.open.is_some() {
+                    Theme::STATUS_ERROR
+                } else {
+                    Theme::INSPECTOR_TEXT_FG
+                }),
             ),
         ])
         .left_aligned(),
         Line::from(vec![
             Span::styled(
-                format!("{:<8}", "Checks:"),
+                format!("{:<11}", "MTTR:"),
                 Style::default().fg(Theme::INSPECTOR_TEXT_FG),
             ),
-            // No style for the number of checks
-            Span::raw(stats.number_of_checks.to_string()),
+            Span::raw(format_mean(incidents.mttr())),
         ])
         .left_aligned(),
         Line::from(vec![
             Span::styled(
-                format!("{:<8}", "Fails:"),
+                format!("{:<11}", "MTBF:"),
                 Style::default().fg(Theme::INSPECTOR_TEXT_FG),
             ),
+            Span::raw(format_mean(incidents.mtbf())),
+        ])
+        .left_aligned(),
+    ]);
+
+    lines.extend(create_slo_lines(endpoint_state, slo));
+
+    lines
+}
+
+/// The SLO with the error budget left and the burn rates.
+fn create_slo_lines(
+    endpoint_state: &EndpointState,
+    status: Option<SloStatus>,
+) -> Vec<Line<'static>> {
+    let label = |text: &str| {
+        Span::styled(
+            format!("{:<8}", text),
+            Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+        )
+    };
+
+    let Some(slo) = endpoint_state.slo else {
+        return vec![Line::from(vec![label("SLO:"), Span::raw("-")]).left_aligned()];
+    };
+
+    // Shared SLOs name the group their budget is measured over
+    let objective = match &endpoint_state.slo_group {
+        Some(group) => format!("{}% / {} ({group})", slo.target, slo.window),
+        None => format!("{}% / {}", slo.target, slo.window),
+    };
+
+    let Some(status) = status else {
+        return vec![Line::from(vec![label("SLO:"), Span::raw(objective)]).left_aligned()];
+    };
+
+    let budget_color = if status.is_fast_burn() {
+        Theme::STATUS_ERROR
+    } else {
+        Theme::budget_color(Some(status.budget_remaining))
+    };
+
+    let format_rate = |rate: Option<f64>| match rate {
+        Some(rate) => format!("{:.1}x", rate),
+        None => "-".to_string(),
+    };
+    let mut burn = format!(
+        "{} 1h · {} 6h",
+        format_rate(status.burn_rate_1h),
+        format_rate(status.burn_rate_6h)
+    );
+    if status.is_fast_burn() {
+        burn = format!("{burn} {}", Theme::FAST_BURN_SYMBOL);
+    }
+
+    vec![
+        Line::from(vec![label("SLO:"), Span::raw(objective)]).left_aligned(),
+        Line::from(vec![
+            label("Budget:"),
             Span::styled(
-                stats.number_of_fails.to_string(),
-                Style::default().fg(fail_color),
+                format!("{:.1}% left", status.budget_remaining),
+                Style::default()
+                    .fg(budget_color)
+                    .add_modifier(Modifier::BOLD),
             ),
+            Span::raw(format!(" ({:.1}% used)", status.budget_consumed)),
+        ])
+        .left_aligned(),
+        Line::from(vec![
+            label("Burn:"),
+            Span::styled(burn, Style::default().fg(budget_color)),
         ])
         .left_aligned(),
     ]
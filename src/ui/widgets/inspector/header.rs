@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 use ratatui::{
     Frame,
     layout::Rect,
@@ -7,21 +9,30 @@ use ratatui::{
 };
 
 use crate::{
+    silence::{Silence, SilenceKind},
     state::EndpointState,
     ui::{theme::Theme, util},
 };
 
-pub fn render_inspector_header(endpoint_state: &EndpointState, frame: &mut Frame, area: Rect) {
+pub fn render_inspector_header(
+    endpoint_state: &EndpointState,
+    silence: Option<&Silence>,
+    frame: &mut Frame,
+    area: Rect,
+) {
     let block = Block::new();
-    let header_lines = create_header_lines(endpoint_state);
+    let header_lines = create_header_lines(endpoint_state, silence);
 
     let inspector_header = Paragraph::new(header_lines).block(block).centered();
 
-    frame.render_widget(inspector_header, util::centered_rect(90, 50, area));
+    frame.render_widget(inspector_header, util::centered_rect(90, 75, area));
 }
 
-fn create_header_lines(endpoint_state: &EndpointState) -> Vec<Line<'static>> {
-    vec![
+fn create_header_lines(
+    endpoint_state: &EndpointState,
+    silence: Option<&Silence>,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![
         Line::from(vec![
             Span::styled("URL:    ", Style::default().fg(Theme::INSPECTOR_TEXT_FG)),
             Span::styled(
@@ -42,5 +53,42 @@ fn create_header_lines(endpoint_state: &EndpointState) -> Vec<Line<'static>> {
             ),
         ])
         .left_aligned(),
-    ]
+    ];
+
+    if let Some(silence) = silence {
+        lines.push(create_silence_line(silence));
+    }
+
+    lines
+}
+
+/// Who's on it, e.g. "✔ Acked 5m ago: rolling back the deploy".
+fn create_silence_line(silence: &Silence) -> Line<'static> {
+    let now = SystemTime::now();
+    let ago = util::format_duration(now.duration_since(silence.since()).unwrap_or_default());
+
+    let text = match &silence.kind {
+        SilenceKind::Ack { note: Some(note) } => {
+            format!("{} Acked {ago} ago: {note}", Theme::ACK_SYMBOL)
+        }
+        SilenceKind::Ack { note: None } => format!("{} Acked {ago} ago", Theme::ACK_SYMBOL),
+        SilenceKind::Snooze { .. } => {
+            let left = silence
+                .until()
+                .and_then(|until| until.duration_since(now).ok())
+                .unwrap_or_default();
+            format!(
+                "{} Snoozed, {} left",
+                Theme::SNOOZE_SYMBOL,
+                util::format_duration(left)
+            )
+        }
+        SilenceKind::Mute => format!("{} Muted {ago} ago", Theme::MUTE_SYMBOL),
+    };
+
+    Line::from(vec![
+        Span::styled("Silence: ", Style::default().fg(Theme::INSPECTOR_TEXT_FG)),
+        Span::styled(text, Style::default().fg(Theme::STATUS_SILENCED)),
+    ])
+    .left_aligned()
 }
@@ -1,21 +1,34 @@
 use std::time::Duration;
 
+use chrono::{DateTime, Local};
+
 use ratatui::{
     Frame,
     layout::Rect,
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Paragraph},
 };
 
 use crate::{
+    config::AnomalyConfig,
     state::EndpointState,
     ui::{theme::Theme, util},
 };
 
-pub fn render_latency_stats(endpoint_state: &EndpointState, frame: &mut Frame, area: Rect) {
+// Column widths so the recent and lifetime values line up
+const LABEL_WIDTH: usize = 8;
+const VALUE_WIDTH: usize = 9;
+
+pub fn render_latency_stats(
+    endpoint_state: &EndpointState,
+    anomaly_config: &AnomalyConfig,
+    frame: &mut Frame,
+    area: Rect,
+) {
     let block = Block::new();
-    let latency_stats_lines = create_latency_stats_lines(endpoint_state);
+    let mut latency_stats_lines = create_latency_stats_lines(endpoint_state);
+    latency_stats_lines.extend(create_baseline_lines(endpoint_state, anomaly_config));
     let h = latency_stats_lines.len() as u16;
     let w = latency_stats_lines
         .iter()
@@ -29,44 +42,133 @@ pub fn render_latency_stats(endpoint_state: &EndpointState, frame: &mut Frame, a
 }
 
 fn create_latency_stats_lines(endpoint_state: &EndpointState) -> Vec<Line<'static>> {
-    let latency_stats = &endpoint_state.latency_stats;
+    let window = endpoint_state.latency.window();
+    let lifetime = endpoint_state.latency.lifetime();
 
     // Helper closure to format and colorize a single latency value
     let format_latency = |val: Option<u64>| -> Span {
         match val {
             Some(ms) => {
-                let color = Theme::latency_color(&Duration::from_millis(ms));
-                Span::styled(format!("{}ms", ms), Style::default().fg(color))
+                let color =
+                    Theme::latency_color(&Duration::from_millis(ms), &endpoint_state.thresholds);
+                Span::styled(
+                    format!("{:<VALUE_WIDTH$}", format!("{}ms", ms)),
+                    Style::default().fg(color),
+                )
             }
-            None => Span::styled("-", Style::default().fg(Theme::INSPECTOR_TEXT_FG)),
+            None => Span::styled(
+                format!("{:<VALUE_WIDTH$}", "-"),
+                Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+            ),
         }
     };
 
-    vec![
+    let format_stddev = |val: Option<f64>| -> Span {
+        let text = match val {
+            Some(ms) => format!("±{:.1}ms", ms),
+            None => "-".to_string(),
+        };
+        Span::styled(
+            format!("{:<VALUE_WIDTH$}", text),
+            Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+        )
+    };
+
+    // Pad labels so numbers align vertically
+    let label = |text: &str| -> Span {
+        Span::styled(
+            format!("{:<LABEL_WIDTH$}", text),
+            Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+        )
+    };
+
+    let rows = [
+        ("Min:", window.min, lifetime.min),
+        ("P50:", window.p50, lifetime.p50),
+        ("P90:", window.p90, lifetime.p90),
+        ("P95:", window.p95, lifetime.p95),
+        ("P99:", window.p99, lifetime.p99),
+        ("Max:", window.max, lifetime.max),
+        ("Avg:", window.avg, lifetime.avg),
+    ];
+
+    let header_style = Style::default()
+        .fg(Theme::INSPECTOR_TEXT_FG)
+        .add_modifier(Modifier::UNDERLINED);
+
+    let mut lines = vec![
         Line::from(vec![
-            // Pad label to 5 chars so numbers align vertically
-            Span::styled(
-                format!("{:<5}", "Min:"),
-                Style::default().fg(Theme::INSPECTOR_TEXT_FG),
-            ),
-            format_latency(latency_stats.min),
+            label(""),
+            Span::styled(format!("{:<VALUE_WIDTH$}", "Recent"), header_style),
+            Span::styled(format!("{:<VALUE_WIDTH$}", "All"), header_style),
         ])
         .left_aligned(),
+    ];
+
+    lines.extend(rows.into_iter().map(|(text, recent, all)| {
         Line::from(vec![
-            Span::styled(
-                format!("{:<5}", "Max:"),
-                Style::default().fg(Theme::INSPECTOR_TEXT_FG),
-            ),
-            format_latency(latency_stats.max),
+            label(text),
+            format_latency(recent),
+            format_latency(all),
         ])
-        .left_aligned(),
+        .left_aligned()
+    }));
+
+    lines.push(
         Line::from(vec![
-            Span::styled(
-                format!("{:<5}", "Avg:"),
-                Style::default().fg(Theme::INSPECTOR_TEXT_FG),
-            ),
-            format_latency(latency_stats.avg),
+            label("StdDev:"),
+            format_stddev(window.stddev),
+            format_stddev(lifetime.stddev),
         ])
         .left_aligned(),
+    );
+
+    lines
+}
+
+/// The learned baseline and how far the latest check was from it.
+fn create_baseline_lines(
+    endpoint_state: &EndpointState,
+    anomaly_config: &AnomalyConfig,
+) -> Vec<Line<'static>> {
+    let label = |text: &str| -> Span<'static> {
+        Span::styled(
+            format!("{:<LABEL_WIDTH$}", text),
+            Style::default().fg(Theme::INSPECTOR_TEXT_FG),
+        )
+    };
+    let text_style = Style::default().fg(Theme::INSPECTOR_TEXT_FG);
+
+    let baseline = &endpoint_state.baseline;
+    let Some(mean) = baseline.mean(anomaly_config) else {
+        let text = if anomaly_config.enabled {
+            "learning..."
+        } else {
+            "disabled"
+        };
+        return vec![
+            Line::from(vec![label("Base:"), Span::styled(text, text_style)]).left_aligned(),
+            Line::from(vec![label("Dev:"), Span::styled("-", text_style)]).left_aligned(),
+        ];
+    };
+
+    let base = format!("{:.0}ms ±{:.1}ms", mean, baseline.stddev());
+
+    let deviation = match (baseline.deviation(), baseline.anomaly_since()) {
+        (Some(dev), Some(since)) => Span::styled(
+            format!(
+                "{:+.1}σ since {}",
+                dev,
+                DateTime::<Local>::from(since).format("%H:%M:%S")
+            ),
+            Style::default().fg(Theme::STATUS_ANOMALY),
+        ),
+        (Some(dev), None) => Span::styled(format!("{:+.1}σ", dev), text_style),
+        (None, _) => Span::styled("-", text_style),
+    };
+
+    vec![
+        Line::from(vec![label("Base:"), Span::styled(base, text_style)]).left_aligned(),
+        Line::from(vec![label("Dev:"), deviation]).left_aligned(),
     ]
 }
@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 use chrono::{DateTime, Local};
 use ratatui::{
     Frame,
@@ -7,7 +9,9 @@ use ratatui::{
     widgets::{Block, Row, Table},
 };
 
-use crate::{backend::CheckStatus, state::EndpointState, ui::theme::Theme};
+use crate::{
+    backend::CheckStatus, notify::hook::HookOutcome, state::EndpointState, ui::theme::Theme,
+};
 
 /// Render a logs panel for an endpoint.
 pub fn render_log(
@@ -49,31 +53,47 @@ pub fn render_log(
     frame.render_widget(log_table, area);
 }
 
+/// The recent checks and hook runs, newest first.
 fn create_log_lines(endpoint_state: &EndpointState) -> Vec<Row<'static>> {
+    let checks = create_check_lines(endpoint_state);
+    let hooks = endpoint_state
+        .hook_runs
+        .iter()
+        .map(|outcome| (outcome.at, create_hook_line(outcome)));
+
+    let mut lines: Vec<(SystemTime, Row)> = checks.chain(hooks).collect();
+    lines.sort_by(|(a, _), (b, _)| b.cmp(a));
+    lines.into_iter().map(|(_, row)| row).collect()
+}
+
+fn format_time(at: SystemTime) -> Line<'static> {
+    let datetime: DateTime<Local> = at.into();
+    let time_str = datetime.format("%H:%M:%S").to_string();
+    Span::styled(time_str, Style::default().fg(Theme::INSPECTOR_TEXT_FG)).into_centered_line()
+}
+
+fn create_check_lines(
+    endpoint_state: &EndpointState,
+) -> impl Iterator<Item = (SystemTime, Row<'static>)> {
     let style = Style::default();
 
     endpoint_state
         .recent_checks
         .iter()
-        .map(|(st, result)| {
+        .map(move |(st, result, _)| {
             // Format the time
-            let datetime: DateTime<Local> = (*st).into();
-            let time_str = datetime.format("%H:%M:%S").to_string();
-            let time_span =
-                Span::styled(time_str, style.fg(Theme::INSPECTOR_TEXT_FG)).into_centered_line();
+            let time_span = format_time(*st);
 
             // Format the status
             let (status_code_str, status_message_str, status_color) = match &result.status {
                 CheckStatus::Success { code, text } => (
                     format!("{:<3}", code),
-                    format!("{}", text),
-                    Theme::color_code(&code),
-                ),
-                CheckStatus::Error { message } => (
-                    "ERR".to_string(),
-                    format!("{}", message),
-                    Theme::STATUS_ERROR,
+                    text.to_string(),
+                    Theme::color_code(code),
                 ),
+                CheckStatus::Error { message } => {
+                    ("ERR".to_string(), message.to_string(), Theme::STATUS_ERROR)
+                }
             };
             let status_code_span =
                 Span::styled(status_code_str, style.fg(status_color)).into_centered_line();
@@ -83,12 +103,12 @@ fn create_log_lines(endpoint_state: &EndpointState) -> Vec<Row<'static>> {
             // Format the latency
             let (latency_str, latency_color) = (
                 format!("{}ms", &result.latency.as_millis()),
-                Theme::latency_color(&result.latency),
+                Theme::latency_color(&result.latency, &endpoint_state.thresholds),
             );
             let latency_span =
                 Span::styled(latency_str, style.fg(latency_color)).into_centered_line();
 
-            Row::new(vec![
+            let row = Row::new(vec![
                 time_span,
                 Line::from("|"),
                 status_code_span,
@@ -96,7 +116,31 @@ fn create_log_lines(endpoint_state: &EndpointState) -> Vec<Row<'static>> {
                 status_message_span,
                 Line::from("|"),
                 latency_span,
-            ])
+            ]);
+            (*st, row)
         })
-        .collect()
+}
+
+/// A hook run, the latency column shows how long the command ran.
+fn create_hook_line(outcome: &HookOutcome) -> Row<'static> {
+    let style = Style::default();
+    let color = if outcome.is_success() {
+        Theme::STATUS_OK
+    } else {
+        Theme::STATUS_ERROR
+    };
+
+    Row::new(vec![
+        format_time(outcome.at),
+        Line::from("|"),
+        Span::styled("HOOK", style.fg(color)).into_centered_line(),
+        Line::from("|"),
+        Span::styled(outcome.describe(), style.fg(color)).into_centered_line(),
+        Line::from("|"),
+        Span::styled(
+            format!("{}ms", outcome.duration.as_millis()),
+            style.fg(Theme::INSPECTOR_TEXT_FG),
+        )
+        .into_centered_line(),
+    ])
 }
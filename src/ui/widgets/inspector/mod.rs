@@ -3,6 +3,8 @@ mod header;
 mod latency;
 mod log;
 
+use std::time::SystemTime;
+
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
@@ -37,8 +39,8 @@ pub fn render_inspector(frame: &mut Frame, app: &mut App, chunk: Rect) {
     let inner_area = chunk.inner(Margin::new(1, 1));
 
     // Render the outer border block
-    let status_color = util::get_status_color(&endpoint_state.latest_status);
-    let outer_block = create_title_block(&endpoint_name, status_color);
+    let status_color = util::get_status_color(&endpoint_state.latest_status, endpoint_state.paused);
+    let outer_block = create_title_block(endpoint_name, status_color);
     render_outer_block(frame, outer_block, outer_area);
 
     // Split the inner area into 3 chunks (with 2 separators)
@@ -52,7 +54,7 @@ pub fn render_inspector(frame: &mut Frame, app: &mut App, chunk: Rect) {
         [
             Constraint::Length(4),
             Constraint::Length(1),
-            Constraint::Length(5),
+            Constraint::Length(13),
             Constraint::Length(1),
             Constraint::Min(10),
         ],
@@ -72,23 +74,33 @@ pub fn render_inspector(frame: &mut Frame, app: &mut App, chunk: Rect) {
     .split(layout[2]);
 
     // Top: Header
-    header::render_inspector_header(&endpoint_state, frame, layout[0]);
+    let silence = app
+        .silences
+        .get(endpoint_name)
+        .filter(|silence| !silence.is_expired(SystemTime::now()));
+    header::render_inspector_header(endpoint_state, silence, frame, layout[0]);
 
     render_stats_separator(frame, layout[1], status_color);
 
     // Middle: Latency Stats | Availability Stats
-    latency::render_latency_stats(&endpoint_state, frame, stats_layout[0]);
+    latency::render_latency_stats(
+        endpoint_state,
+        &app.config.anomaly_detection,
+        frame,
+        stats_layout[0],
+    );
     let stats_separator = Block::default()
         .borders(Borders::LEFT)
         .border_set(Theme::PANEL_BORDER)
         .border_style(status_color);
     frame.render_widget(stats_separator, stats_layout[1]);
-    availability::render_availability_stats(&endpoint_state, frame, stats_layout[2]);
+    let slo = app.slo_status(endpoint_state, SystemTime::now());
+    availability::render_availability_stats(endpoint_state, slo, frame, stats_layout[2]);
 
     render_logs_separator(frame, layout[3], status_color);
 
     // Bottom: Recent Activities (Logs)
-    log::render_log(&endpoint_state, frame, layout[4], status_color);
+    log::render_log(endpoint_state, frame, layout[4], status_color);
 }
 
 fn render_outer_block(frame: &mut Frame, outer_block: Block, area: Rect) {
@@ -1,4 +1,7 @@
+pub mod alerts;
 pub mod footer;
+pub mod form;
+pub mod incidents;
 pub mod inspector;
 pub mod table;
 pub mod welcome;
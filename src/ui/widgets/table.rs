@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime};
+
 use ratatui::{
     layout::Constraint,
     prelude::*,
@@ -6,15 +8,22 @@ use ratatui::{
 };
 
 use crate::{backend::CheckStatus, ui::theme::Theme};
-use crate::{state::App, ui::util};
+use crate::{
+    state::{App, EndpointState},
+    ui::util,
+};
 
 const SPARKLINE_LENGTH: usize = 15;
 
 pub fn render_table(frame: &mut Frame, app: &mut App, chunk: Rect) {
+    let uptime_header = format!("UPTIME {}", app.config.uptime_window);
     let header = Row::new(vec![
         Line::from("NAME").centered(),
         Line::from("STATUS").centered(),
         Line::from("LATENCY").centered(),
+        Line::from("P95").centered(),
+        Line::from(uptime_header).centered(),
+        Line::from("BUDGET").centered(),
         Line::from("TREND").centered(),
     ])
     .style(
@@ -24,13 +33,16 @@ pub fn render_table(frame: &mut Frame, app: &mut App, chunk: Rect) {
             .add_modifier(Modifier::UNDERLINED),
     );
 
-    let rows = create_rows(&app);
+    let rows = create_rows(app);
 
     let widths = vec![
-        Constraint::Percentage(30),
-        Constraint::Percentage(25),
-        Constraint::Percentage(15),
-        Constraint::Percentage(30),
+        Constraint::Percentage(20),
+        Constraint::Percentage(20),
+        Constraint::Percentage(10),
+        Constraint::Percentage(10),
+        Constraint::Percentage(11),
+        Constraint::Percentage(11),
+        Constraint::Percentage(18),
     ];
 
     let title =
@@ -55,33 +67,93 @@ pub fn render_table(frame: &mut Frame, app: &mut App, chunk: Rect) {
 fn create_rows(app: &App) -> Vec<Row<'static>> {
     let mut rows: Vec<Row> = Vec::new();
     let selected_idx = app.table_state.selected();
+    let now = SystemTime::now();
 
     for (i, endpoint_name) in app.endpoint_order.iter().enumerate() {
-        let Some(state) = app.endpoint_states.get(endpoint_name) else {
-            continue;
-        };
+        let is_selected = Some(i) == selected_idx;
 
-        let Some(status) = &state.latest_status else {
+        let Some(state) = app.endpoint_states.get(endpoint_name) else {
             continue;
         };
 
-        let Some(latency) = &state.latest_latency else {
+        // Endpoints that haven't reported yet are left out, unless they're
+        // paused in which case we still want to show the paused indicator.
+        let (Some(status), Some(latency)) = (&state.latest_status, &state.latest_latency) else {
+            if state.paused {
+                let name_cell = create_name_cell(app, state, now);
+                rows.push(create_paused_row(name_cell, is_selected));
+            }
             continue;
         };
 
         // If we reach this point, we are guaranteed to have
         // 'state', 'status', and 'latency' so we build up our rows.
         // Create an appropriate status message and get its color.
-        let (status_message, status_color) = match status {
-            CheckStatus::Success { code, text } => {
-                let color = Theme::color_code(code);
-                (format!("{:<3} {}", code, text), color)
+        let (status_message, status_color) = if state.paused {
+            (
+                format!("{} PAUSED", Theme::PAUSED_SYMBOL),
+                Theme::STATUS_PAUSED,
+            )
+        } else if state.flap.is_flapping() {
+            (
+                format!("{} FLAPPING", Theme::FLAPPING_SYMBOL),
+                Theme::STATUS_FLAPPING,
+            )
+        } else {
+            match status {
+                CheckStatus::Success { code, text } => {
+                    let color = Theme::color_code(code);
+                    (format!("{:<3} {}", code, text), color)
+                }
+                CheckStatus::Error { message } => (format!("ERR {}", message), Theme::STATUS_ERROR),
             }
-            CheckStatus::Error { message } => (format!("ERR {}", message), Theme::STATUS_ERROR),
         };
 
+        // Paused rows keep their last latency but greyed out since it's stale.
         let latency_message = format!("{}ms", latency.as_millis());
-        let latency_color = Theme::latency_color(latency);
+        let latency_color = if state.paused {
+            Theme::STATUS_PAUSED
+        } else {
+            Theme::latency_color(latency, &state.thresholds)
+        };
+
+        // P95 over the recent checks, greyed out like the latency when paused
+        let (p95_message, p95_color) = match state.latency.window().p95 {
+            Some(ms) if !state.paused => (
+                format!("{}ms", ms),
+                Theme::latency_color(&Duration::from_millis(ms), &state.thresholds),
+            ),
+            Some(ms) => (format!("{}ms", ms), Theme::STATUS_PAUSED),
+            None => ("-".to_string(), Theme::STATUS_PAUSED),
+        };
+
+        let uptime = state
+            .availability_stats
+            .uptime_percent(app.config.uptime_window, now);
+        let uptime_message = match uptime {
+            Some(uptime) => format!("{:.2}%", uptime),
+            None => "-".to_string(),
+        };
+        let uptime_color = if state.paused {
+            Theme::STATUS_PAUSED
+        } else {
+            Theme::uptime_color(uptime)
+        };
+
+        // Error budget left, only for endpoints with an SLO
+        let slo_status = app.slo_status(state, now);
+        let budget_message = match &slo_status {
+            Some(slo) if slo.is_fast_burn() => {
+                format!("{} {:.1}%", Theme::FAST_BURN_SYMBOL, slo.budget_remaining)
+            }
+            Some(slo) => format!("{:.1}%", slo.budget_remaining),
+            None => "-".to_string(),
+        };
+        let budget_color = match &slo_status {
+            _ if state.paused => Theme::STATUS_PAUSED,
+            Some(slo) if slo.is_fast_burn() => Theme::STATUS_ERROR,
+            _ => Theme::budget_color(slo_status.map(|slo| slo.budget_remaining)),
+        };
 
         // Take the last 'SPARKLINE_LENGTH' data points from the latency_history
         // and create a sparkline string.
@@ -90,8 +162,14 @@ fn create_rows(app: &App) -> Vec<Row<'static>> {
         let latency_slice: Vec<u64> = state.latency_history.iter().skip(start).copied().collect();
         let sparkline = generate_sparkline_string(&latency_slice);
 
+        // The trend stands out while the latency is off its usual baseline
+        let sparkline_color = if state.baseline.is_anomalous() && !state.paused {
+            Theme::STATUS_ANOMALY
+        } else {
+            latency_color
+        };
+
         // Handle selected row color reversal without reversing the sparkline cell
-        let is_selected = Some(i) == selected_idx;
         let cell_style = if is_selected {
             Style::default().add_modifier(Modifier::REVERSED)
         } else {
@@ -100,12 +178,17 @@ fn create_rows(app: &App) -> Vec<Row<'static>> {
 
         rows.push(
             Row::new(vec![
-                Cell::from(state.name.clone()).style(cell_style),
+                create_name_cell(app, state, now).style(cell_style),
                 Cell::from(Line::from(status_message).left_aligned())
                     .style(cell_style.fg(status_color)),
                 Cell::from(Line::from(latency_message).centered())
                     .style(cell_style.fg(latency_color)),
-                Cell::from(sparkline).fg(latency_color),
+                Cell::from(Line::from(p95_message).centered()).style(cell_style.fg(p95_color)),
+                Cell::from(Line::from(uptime_message).centered())
+                    .style(cell_style.fg(uptime_color)),
+                Cell::from(Line::from(budget_message).centered())
+                    .style(cell_style.fg(budget_color)),
+                Cell::from(sparkline).fg(sparkline_color),
             ])
             .height(1),
         );
@@ -113,6 +196,43 @@ fn create_rows(app: &App) -> Vec<Row<'static>> {
     rows
 }
 
+/// The endpoint name along with its ack/snooze/mute badge.
+fn create_name_cell(app: &App, state: &EndpointState, now: SystemTime) -> Cell<'static> {
+    let mut spans = vec![Span::raw(state.name.clone())];
+
+    if let Some(silence) = app.silences.get(&state.name)
+        && !silence.is_expired(now)
+    {
+        spans.push(Span::styled(
+            format!(" {}", util::silence_badge(silence, now)),
+            Style::default().fg(Theme::STATUS_SILENCED),
+        ));
+    }
+
+    Cell::from(Line::from(spans))
+}
+
+/// Row for a paused endpoint that hasn't reported any results yet.
+fn create_paused_row(name_cell: Cell<'static>, is_selected: bool) -> Row<'static> {
+    let cell_style = if is_selected {
+        Style::default().add_modifier(Modifier::REVERSED)
+    } else {
+        Style::default()
+    };
+
+    Row::new(vec![
+        name_cell.style(cell_style),
+        Cell::from(Line::from(format!("{} PAUSED", Theme::PAUSED_SYMBOL)).left_aligned())
+            .style(cell_style.fg(Theme::STATUS_PAUSED)),
+        Cell::from(Line::from("-").centered()).style(cell_style.fg(Theme::STATUS_PAUSED)),
+        Cell::from(Line::from("-").centered()).style(cell_style.fg(Theme::STATUS_PAUSED)),
+        Cell::from(Line::from("-").centered()).style(cell_style.fg(Theme::STATUS_PAUSED)),
+        Cell::from(Line::from("-").centered()).style(cell_style.fg(Theme::STATUS_PAUSED)),
+        Cell::from(" "),
+    ])
+    .height(1)
+}
+
 /// Helper function to create sparkline strings
 fn generate_sparkline_string(data: &[u64]) -> String {
     if data.is_empty() {
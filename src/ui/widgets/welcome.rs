@@ -47,7 +47,17 @@ pub fn render_welcome_message(frame: &mut Frame) {
             Style::default().fg(Theme::DESC_FG),
         )),
         Line::from(""),
-        Line::from("Add your endpoints to the file and restart the app."),
+        Line::from("Add your endpoints to the file and they will be picked up automatically."),
+        Line::from(vec![
+            Span::raw("You can also press "),
+            Span::styled(
+                "a",
+                Style::default()
+                    .fg(Theme::BORDER_FOCUSED)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to add one from here."),
+        ]),
         Line::from(""),
         Line::from(vec![
             Span::raw("Press "),
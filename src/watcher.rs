@@ -0,0 +1,62 @@
+use std::{fs, path::PathBuf, time::Duration, time::SystemTime};
+
+use tokio::{sync::mpsc::Sender, time::sleep};
+
+use crate::config::{ConfigPaths, StatuiConfig};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Editors often write a file in several steps (truncate, write, rename),
+// so we give them a moment to finish before reading it back.
+const SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+/// Events sent by the config watcher to the UI.
+#[derive(Debug)]
+pub enum ConfigEvent {
+    Reloaded(Box<StatuiConfig>),
+    Error(String),
+}
+
+/// Watches the config files for changes and sends the reloaded config
+/// (or the error that came up while loading it) to the UI.
+///
+/// Files are polled for their modification time instead of relying on
+/// filesystem notifications, which behave differently on every platform
+/// and with every editor's save strategy.
+pub async fn watch_config(paths: ConfigPaths, tx: Sender<ConfigEvent>) {
+    let mut last_modified = modified_times(&paths);
+
+    loop {
+        sleep(POLL_INTERVAL).await;
+
+        let modified = modified_times(&paths);
+        if modified == last_modified {
+            continue;
+        }
+
+        sleep(SETTLE_DELAY).await;
+        last_modified = modified_times(&paths);
+
+        let event = match StatuiConfig::load(&paths) {
+            Ok(config) => ConfigEvent::Reloaded(Box::new(config)),
+            Err(err) => ConfigEvent::Error(err.to_string()),
+        };
+
+        // The UI is gone, nothing left to watch for.
+        if tx.send(event).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Modification times of the config files, None for the ones that don't exist.
+fn modified_times(paths: &ConfigPaths) -> Vec<(PathBuf, Option<SystemTime>)> {
+    paths
+        .all()
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+            (path.clone(), modified)
+        })
+        .collect()
+}